@@ -4,14 +4,14 @@ use std::fs;
 use std::io::Read;
 use transitfeed::{
     Agency, Calendar, CalendarDate, FareAttribute, FareRule, FeedInfo, Frequency, GTFSIterator,
-    Route, ShapePoint, Stop, StopTime, Transfer, Trip,
+    GTFSRecordReader, Route, ShapePoint, Stop, StopTime, StopTimeRef, Transfer, Trip,
 };
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 
 const INPUT_FOLDER: &'static str = "examples/bench";
 
-criterion_group!(gtfs, bench_feed_throughput,);
+criterion_group!(gtfs, bench_feed_throughput, bench_stop_times_borrowed);
 criterion_main!(gtfs);
 
 fn bench_feed_throughput(c: &mut Criterion) {
@@ -52,6 +52,35 @@ fn bench_file_throughput<T: for<'de> serde::Deserialize<'de>>(
     );
 }
 
+fn bench_stop_times_borrowed(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stop_times");
+    let file = "stop_times.txt";
+    let data = file_to_mem(&format!("{}/{}", INPUT_FOLDER, file));
+    group.throughput(criterion::Throughput::Bytes(data.len() as u64));
+    group.bench_with_input(criterion::BenchmarkId::new("owned", file), &data, |b, i| {
+        b.iter(|| {
+            let csv = Reader::from_reader(&i[..]);
+            let iterator: GTFSIterator<_, StopTime> = GTFSIterator::new(csv, file).unwrap();
+            for thing in iterator {
+                let _ = thing;
+            }
+        })
+    });
+    group.bench_with_input(
+        criterion::BenchmarkId::new("borrowed", file),
+        &data,
+        |b, i| {
+            b.iter(|| {
+                let csv = Reader::from_reader(&i[..]);
+                let mut records = GTFSRecordReader::new(csv, file).unwrap();
+                while let Some(thing) = records.read::<StopTimeRef>() {
+                    let _ = thing;
+                }
+            })
+        },
+    );
+}
+
 fn file_to_mem(fp: &str) -> Vec<u8> {
     let mut f = or_die(fs::File::open(fp));
     let mut bs = vec![];
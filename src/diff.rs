@@ -0,0 +1,368 @@
+//! What changed between two versions of a feed
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+
+use chrono::NaiveDate;
+
+use crate::feed::Feed;
+use crate::{Calendar, CalendarDate, Frequency, Route, ServiceCalendar, Stop, StopTime, Trip};
+
+/// A field whose value differs between the two versions of a record,
+/// with both values in their `Debug` form
+#[derive(Debug, PartialEq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub before: String,
+    pub after: String,
+}
+
+impl FieldChange {
+    fn new(field: &'static str, before: &impl Debug, after: &impl Debug) -> Self {
+        FieldChange {
+            field,
+            before: format!("{:?}", before),
+            after: format!("{:?}", after),
+        }
+    }
+}
+
+/// The fields of `$before` and `$after` that differ
+macro_rules! field_changes {
+    ($before:expr, $after:expr, $($field:ident),+) => {{
+        let mut changes = Vec::new();
+        $(
+            if $before.$field != $after.$field {
+                changes.push(FieldChange::new(
+                    stringify!($field),
+                    &$before.$field,
+                    &$after.$field,
+                ));
+            }
+        )+
+        changes
+    }};
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// Change
+/// A record added, removed or modified, keyed by its ID. `fields` lists
+/// what differs for modified records and is empty otherwise.
+#[derive(Debug)]
+pub struct Change<'a, T> {
+    pub id: &'a str,
+    pub before: Option<T>,
+    pub after: Option<T>,
+    pub fields: Vec<FieldChange>,
+}
+
+impl<'a, T> Change<'a, T> {
+    pub fn kind(&self) -> ChangeKind {
+        match (&self.before, &self.after) {
+            (None, _) => ChangeKind::Added,
+            (_, None) => ChangeKind::Removed,
+            _ => ChangeKind::Modified,
+        }
+    }
+}
+
+/// Service
+/// A service_id with its calendar.txt row and calendar_dates.txt rows in
+/// date order
+#[derive(Debug, Default, PartialEq)]
+pub struct Service<'a> {
+    pub calendar: Option<&'a Calendar>,
+    pub dates: Vec<&'a CalendarDate>,
+}
+
+impl<'a> Service<'a> {
+    /// The services of `feed` by service_id
+    pub(crate) fn by_id(feed: &'a Feed) -> HashMap<&'a str, Service<'a>> {
+        let mut services: HashMap<&str, Service> = HashMap::new();
+        for calendar in &feed.calendars {
+            services.entry(&calendar.service_id).or_default().calendar = Some(calendar);
+        }
+        for date in &feed.calendar_dates {
+            services
+                .entry(&date.service_id)
+                .or_default()
+                .dates
+                .push(date);
+        }
+        for service in services.values_mut() {
+            service.dates.sort_by_key(|d| d.date);
+        }
+        services
+    }
+}
+
+/// How many trips of a route run on a date in each version of the feed,
+/// see `FeedDiff::schedule_changes`
+#[derive(Debug, PartialEq)]
+pub struct ScheduleChange<'a> {
+    pub route_id: &'a str,
+    pub date: NaiveDate,
+    pub before: usize,
+    pub after: usize,
+}
+
+/// FeedDiff
+/// The stops, routes, trips and services that differ between two versions
+/// of a feed, in ID order. Trips compare their stop times and frequencies
+/// too.
+#[derive(Debug)]
+pub struct FeedDiff<'a> {
+    before: &'a Feed,
+    after: &'a Feed,
+    pub stops: Vec<Change<'a, &'a Stop>>,
+    pub routes: Vec<Change<'a, &'a Route>>,
+    pub trips: Vec<Change<'a, &'a Trip>>,
+    pub services: Vec<Change<'a, Service<'a>>>,
+}
+
+impl<'a> FeedDiff<'a> {
+    pub fn new(before: &'a Feed, after: &'a Feed) -> Self {
+        let stops = changes(
+            before
+                .stops
+                .iter()
+                .map(|s| (s.stop_id.as_str(), s))
+                .collect(),
+            after
+                .stops
+                .iter()
+                .map(|s| (s.stop_id.as_str(), s))
+                .collect(),
+            |a, b| {
+                field_changes!(
+                    a,
+                    b,
+                    stop_code,
+                    stop_name,
+                    stop_desc,
+                    stop_lat,
+                    stop_lon,
+                    zone_id,
+                    stop_url,
+                    location_type,
+                    parent_station,
+                    stop_timezone,
+                    wheelchair_boarding
+                )
+            },
+        );
+        let routes = changes(
+            before
+                .routes
+                .iter()
+                .map(|r| (r.route_id.as_str(), r))
+                .collect(),
+            after
+                .routes
+                .iter()
+                .map(|r| (r.route_id.as_str(), r))
+                .collect(),
+            |a, b| {
+                field_changes!(
+                    a,
+                    b,
+                    agency_id,
+                    route_short_name,
+                    route_long_name,
+                    route_desc,
+                    route_type,
+                    route_url,
+                    route_color,
+                    route_text_color
+                )
+            },
+        );
+
+        let (stop_times_before, stop_times_after) = (stop_times(before), stop_times(after));
+        let (frequencies_before, frequencies_after) = (frequencies(before), frequencies(after));
+        let trips = changes(
+            before
+                .trips
+                .iter()
+                .map(|t| (t.trip_id.as_str(), t))
+                .collect(),
+            after
+                .trips
+                .iter()
+                .map(|t| (t.trip_id.as_str(), t))
+                .collect(),
+            |a, b| {
+                let mut fields = field_changes!(
+                    a,
+                    b,
+                    route_id,
+                    service_id,
+                    trip_headsign,
+                    trip_short_name,
+                    direction_id,
+                    block_id,
+                    shape_id,
+                    wheelchair_accessible,
+                    bikes_allowed
+                );
+                let id = a.trip_id.as_str();
+                let (a, b) = (&stop_times_before.get(id), &stop_times_after.get(id));
+                if a != b {
+                    fields.push(FieldChange::new("stop_times", a, b));
+                }
+                let (a, b) = (&frequencies_before.get(id), &frequencies_after.get(id));
+                if a != b {
+                    fields.push(FieldChange::new("frequencies", a, b));
+                }
+                fields
+            },
+        );
+
+        let services = changes(Service::by_id(before), Service::by_id(after), |a, b| {
+            let mut fields = match (a.calendar, b.calendar) {
+                (Some(a), Some(b)) => field_changes!(
+                    a, b, monday, tuesday, wednesday, thursday, friday, saturday, sunday,
+                    start_date, end_date
+                ),
+                (a, b) if a != b => vec![FieldChange::new("calendar", &a, &b)],
+                _ => Vec::new(),
+            };
+            if a.dates != b.dates {
+                fields.push(FieldChange::new("calendar_dates", &a.dates, &b.dates));
+            }
+            fields
+        });
+
+        FeedDiff {
+            before,
+            after,
+            stops,
+            routes,
+            trips,
+            services,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stops.is_empty()
+            && self.routes.is_empty()
+            && self.trips.is_empty()
+            && self.services.is_empty()
+    }
+
+    /// The routes and `dates` on which a different number of trips runs,
+    /// ordered by route and date. A trip with frequencies counts once per
+    /// departure.
+    pub fn schedule_changes(&self, dates: RangeInclusive<NaiveDate>) -> Vec<ScheduleChange<'a>> {
+        let before = trips_per_day(self.before, dates.clone());
+        let after = trips_per_day(self.after, dates);
+        let keys: BTreeSet<_> = before.keys().chain(after.keys()).copied().collect();
+        keys.into_iter()
+            .filter_map(|key| {
+                let (route_id, date) = key;
+                let before = before.get(&key).copied().unwrap_or(0);
+                let after = after.get(&key).copied().unwrap_or(0);
+                if before == after {
+                    return None;
+                }
+                Some(ScheduleChange {
+                    route_id,
+                    date,
+                    before,
+                    after,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Feed {
+    /// What changed from this feed to `other`, see `FeedDiff`
+    pub fn diff<'a>(&'a self, other: &'a Feed) -> FeedDiff<'a> {
+        FeedDiff::new(self, other)
+    }
+}
+
+/// The records only in `before`, only in `after`, and those whose `fields`
+/// differ, ordered by ID
+fn changes<'a, T>(
+    mut before: HashMap<&'a str, T>,
+    mut after: HashMap<&'a str, T>,
+    fields: impl Fn(&T, &T) -> Vec<FieldChange>,
+) -> Vec<Change<'a, T>> {
+    let ids: BTreeSet<&str> = before.keys().chain(after.keys()).copied().collect();
+    ids.into_iter()
+        .filter_map(|id| {
+            let (before, after) = (before.remove(id), after.remove(id));
+            let fields = match (&before, &after) {
+                (Some(a), Some(b)) => fields(a, b),
+                _ => Vec::new(),
+            };
+            if before.is_some() && after.is_some() && fields.is_empty() {
+                return None;
+            }
+            Some(Change {
+                id,
+                before,
+                after,
+                fields,
+            })
+        })
+        .collect()
+}
+
+/// The stop times of each trip in order
+fn stop_times(feed: &Feed) -> HashMap<&str, Vec<&StopTime>> {
+    let mut trips: HashMap<&str, Vec<&StopTime>> = HashMap::new();
+    for stop_time in &feed.stop_times {
+        trips.entry(&stop_time.trip_id).or_default().push(stop_time);
+    }
+    for stop_times in trips.values_mut() {
+        stop_times.sort_by_key(|st| st.stop_sequence);
+    }
+    trips
+}
+
+fn frequencies(feed: &Feed) -> HashMap<&str, Vec<&Frequency>> {
+    let mut trips: HashMap<&str, Vec<&Frequency>> = HashMap::new();
+    for frequency in &feed.frequencies {
+        trips.entry(&frequency.trip_id).or_default().push(frequency);
+    }
+    for frequencies in trips.values_mut() {
+        frequencies.sort_by_key(|f| f.start_time.clone());
+    }
+    trips
+}
+
+/// The number of trips of each route running on each of `dates`
+fn trips_per_day(
+    feed: &Feed,
+    dates: RangeInclusive<NaiveDate>,
+) -> BTreeMap<(&str, NaiveDate), usize> {
+    let mut departures: HashMap<&str, usize> = HashMap::new();
+    for frequency in &feed.frequencies {
+        let span = (frequency.end_time.duration() - frequency.start_time.duration())
+            .num_seconds()
+            .max(0) as u64;
+        *departures.entry(&frequency.trip_id).or_default() +=
+            span.div_ceil(frequency.headway_secs.max(1)) as usize;
+    }
+    let calendar = ServiceCalendar::new(feed);
+    let mut counts = BTreeMap::new();
+    for date in dates.start().iter_days().take_while(|d| d <= dates.end()) {
+        let services = calendar.active_services(date);
+        for trip in &feed.trips {
+            if services.contains(trip.service_id.as_str()) {
+                let trips = departures.get(trip.trip_id.as_str()).copied().unwrap_or(1);
+                *counts.entry((trip.route_id.as_str(), date)).or_default() += trips;
+            }
+        }
+    }
+    counts
+}
@@ -0,0 +1,261 @@
+//! Distances, projections and outlines of points on the earth given in
+//! degrees
+use std::collections::HashMap;
+
+/// Mean earth radius in meters
+pub const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// Great circle distance in meters between two (lat, lon) points
+pub fn haversine(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat1, lon1) = (from.0.to_radians(), from.1.to_radians());
+    let (lat2, lon2) = (to.0.to_radians(), to.1.to_radians());
+    let a = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * a.sqrt().asin()
+}
+
+/// Degrees of latitude spanning `meters`
+pub fn latitude_degrees(meters: f64) -> f64 {
+    (meters / EARTH_RADIUS).to_degrees()
+}
+
+/// Where along the segment from `a` to `b` the point closest to `p` is, as
+/// a fraction of the segment, and the distance in meters to it. Distances
+/// are measured on a plane tangent at `p`.
+pub fn project_onto_segment(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    let scale = p.0.to_radians().cos();
+    let project = |q: (f64, f64)| {
+        (
+            (q.1 - p.1).to_radians() * scale * EARTH_RADIUS,
+            (q.0 - p.0).to_radians() * EARTH_RADIUS,
+        )
+    };
+    let (a, b) = (project(a), project(b));
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    let t = if length > 0.0 {
+        (-(a.0 * dx + a.1 * dy) / length).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (x, y) = (a.0 + t * dx, a.1 + t * dy);
+    (t, x.hypot(y))
+}
+
+/// Smallest convex polygon around (lat, lon) `points`, counterclockwise and
+/// without repeating the first point
+pub fn convex_hull(points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.total_cmp(&b.0)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let mut hull: Vec<(f64, f64)> = Vec::with_capacity(points.len() + 1);
+    // Lower chain from west to east, then upper chain back
+    for pass in [points.clone(), points.into_iter().rev().collect()].iter() {
+        let start = hull.len();
+        for p in pass {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], *p) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(*p);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+/// A polygon around `points` following their outline more closely than the
+/// convex hull. An edge is dented in towards the nearest point inside when
+/// it is more than `concavity` times longer than the distance to that
+/// point, so smaller values give tighter hulls.
+pub fn concave_hull(points: &[(f64, f64)], concavity: f64) -> Vec<(f64, f64)> {
+    let mut hull = convex_hull(points);
+    if hull.len() < 3 {
+        return hull;
+    }
+    let scale = hull[0].0.to_radians().cos();
+    let xy = |p: (f64, f64)| (p.1 * scale, p.0);
+    let mut inner: Vec<(f64, f64)> = points
+        .iter()
+        .filter(|p| !hull.contains(p))
+        .copied()
+        .collect();
+    inner.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    inner.dedup();
+    let grid = Grid::new(inner.iter().map(|p| xy(*p)).collect());
+    let mut used = vec![false; inner.len()];
+
+    let mut i = 0;
+    while i < hull.len() {
+        let n = hull.len();
+        let (prev, a, b, next) = (
+            xy(hull[(i + n - 1) % n]),
+            xy(hull[i]),
+            xy(hull[(i + 1) % n]),
+            xy(hull[(i + 2) % n]),
+        );
+        // Points further from the edge than its length over `concavity`
+        // cannot dent it
+        let reach = if concavity > 0.0 {
+            distance(a, b) / concavity
+        } else {
+            f64::INFINITY
+        };
+        let nearest = grid
+            .near(a, b, reach)
+            .filter(|&j| !used[j])
+            .map(|j| (j, segment_distance(grid.points[j], a, b)))
+            .filter(|&(j, d)| {
+                let p = grid.points[j];
+                d <= segment_distance(p, prev, a) && d <= segment_distance(p, b, next)
+            })
+            .min_by(|x, y| x.1.total_cmp(&y.1).then(x.0.cmp(&y.0)));
+        if let Some((j, _)) = nearest {
+            let p = grid.points[j];
+            let dent = distance(a, p).min(distance(b, p));
+            let crosses = || {
+                (0..n)
+                    .filter(|&k| k != i && (k + 1) % n != i && k != (i + 1) % n)
+                    .any(|k| {
+                        let (c, d) = (xy(hull[k]), xy(hull[(k + 1) % n]));
+                        segments_cross(a, p, c, d) || segments_cross(p, b, c, d)
+                    })
+            };
+            if dent > 0.0 && distance(a, b) / dent > concavity && !crosses() {
+                used[j] = true;
+                hull.insert(i + 1, inner[j]);
+                continue;
+            }
+        }
+        i += 1;
+    }
+    hull
+}
+
+/// Points bucketed into square cells, so those near an edge can be found
+/// without going through all of them
+struct Grid {
+    points: Vec<(f64, f64)>,
+    cell: f64,
+    /// Corners of the occupied cells
+    min: (i64, i64),
+    max: (i64, i64),
+    cells: HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl Grid {
+    fn new(points: Vec<(f64, f64)>) -> Self {
+        let (mut low, mut high) = ((f64::MAX, f64::MAX), (f64::MIN, f64::MIN));
+        for p in &points {
+            low = (low.0.min(p.0), low.1.min(p.1));
+            high = (high.0.max(p.0), high.1.max(p.1));
+        }
+        // Cells a few points wide when they are spread evenly
+        let span = (high.0 - low.0).max(high.1 - low.1);
+        let cell = span / (points.len() as f64).sqrt();
+        let mut grid = Grid {
+            points: Vec::new(),
+            cell: if cell > 0.0 { cell } else { 1.0 },
+            min: (i64::MAX, i64::MAX),
+            max: (i64::MIN, i64::MIN),
+            cells: HashMap::new(),
+        };
+        for (i, p) in points.iter().enumerate() {
+            let key = grid.key(*p);
+            grid.min = (grid.min.0.min(key.0), grid.min.1.min(key.1));
+            grid.max = (grid.max.0.max(key.0), grid.max.1.max(key.1));
+            grid.cells.entry(key).or_default().push(i);
+        }
+        grid.points = points;
+        grid
+    }
+
+    fn key(&self, p: (f64, f64)) -> (i64, i64) {
+        (
+            (p.0 / self.cell).floor() as i64,
+            (p.1 / self.cell).floor() as i64,
+        )
+    }
+
+    /// Points within the box around `a` and `b` grown by `reach`
+    fn near(&self, a: (f64, f64), b: (f64, f64), reach: f64) -> impl Iterator<Item = usize> + '_ {
+        let low = self.key((a.0.min(b.0) - reach, a.1.min(b.1) - reach));
+        let high = self.key((a.0.max(b.0) + reach, a.1.max(b.1) + reach));
+        let (x0, y0) = (low.0.max(self.min.0), low.1.max(self.min.1));
+        let (x1, y1) = (high.0.min(self.max.0), high.1.min(self.max.1));
+        (x0..=x1)
+            .flat_map(move |x| (y0..=y1).map(move |y| (x, y)))
+            .filter_map(move |key| self.cells.get(&key))
+            .flatten()
+            .copied()
+    }
+}
+
+/// Positive when `o`, `a`, `b` turn counterclockwise, taking longitude as x
+fn cross(o: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.1 - o.1) * (b.0 - o.0) - (a.0 - o.0) * (b.1 - o.1)
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+/// Distance from `p` to the segment from `a` to `b` in the plane
+fn segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    let t = if length > 0.0 {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    distance(p, (a.0 + t * dx, a.1 + t * dy))
+}
+
+/// Whether the segments cross at a point inside both
+fn segments_cross(a: (f64, f64), b: (f64, f64), c: (f64, f64), d: (f64, f64)) -> bool {
+    let side = |p: (f64, f64), q: (f64, f64), r: (f64, f64)| {
+        (q.0 - p.0) * (r.1 - p.1) - (q.1 - p.1) * (r.0 - p.0)
+    };
+    let (d1, d2) = (side(c, d, a), side(c, d, b));
+    let (d3, d4) = (side(a, b, c), side(a, b, d));
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_haversine() {
+        // One degree of latitude is about 111 km
+        let d = haversine((0.0, 0.0), (1.0, 0.0));
+        assert!((d - 111_195.0).abs() < 1.0);
+        assert!((latitude_degrees(d) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_hulls() {
+        // A U shape opening to the north, the convex hull spans the gap
+        let points = [
+            (0.0, 0.0),
+            (0.0, 3.0),
+            (3.0, 3.0),
+            (3.0, 2.0),
+            (1.0, 2.0),
+            (1.0, 1.0),
+            (3.0, 1.0),
+            (3.0, 0.0),
+            (1.0, 0.5),
+        ];
+        let convex = convex_hull(&points);
+        assert_eq!(vec![(0.0, 0.0), (0.0, 3.0), (3.0, 3.0), (3.0, 0.0)], convex);
+        assert_eq!(convex, concave_hull(&points, 10.0));
+        let concave = concave_hull(&points, 0.4);
+        assert!(concave.contains(&(1.0, 1.0)) && concave.contains(&(1.0, 2.0)));
+    }
+}
@@ -1,16 +1,71 @@
-use std::fs::File;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::ops::Deref;
 use std::path::Path;
 use tempfile::{Builder, TempDir};
 
 use super::archive::extract_zip;
+use super::manifest::{requirement, Manifest, ManifestEntry, Requirement, GTFS_FILES};
 use super::{
     Agency, Calendar, CalendarDate, FareAttribute, FareRule, FeedInfo, Frequency, Route,
     ShapePoint, Stop, StopTime, Transfer, Trip,
 };
-use super::{Error, GTFSIterator};
+use super::{Error, GTFSIterator, GTFSRecordReader};
+#[cfg(feature = "rayon")]
+use crate::gtfs::read_chunked;
+#[cfg(feature = "rayon")]
+use std::io::Read;
 
 pub use csv::{Terminator, Trim};
 
+/// Feed
+/// Every table of a feed read into memory. Tables of files that are missing
+/// from the feed and not required by GTFS are left empty.
+#[derive(Debug, Default)]
+pub struct Feed {
+    pub agencies: Vec<Agency>,
+    pub stops: Vec<Stop>,
+    pub routes: Vec<Route>,
+    pub trips: Vec<Trip>,
+    pub stop_times: Vec<StopTime>,
+    pub calendars: Vec<Calendar>,
+    pub calendar_dates: Vec<CalendarDate>,
+    pub fare_attributes: Vec<FareAttribute>,
+    pub fare_rules: Vec<FareRule>,
+    pub shapes: Vec<ShapePoint>,
+    pub frequencies: Vec<Frequency>,
+    pub transfers: Vec<Transfer>,
+    pub feed_info: Vec<FeedInfo>,
+}
+
+impl Feed {
+    /// Points of each shape ordered by shape_pt_sequence
+    pub fn shapes_by_id(&self) -> HashMap<&str, Vec<&ShapePoint>> {
+        let mut shapes: HashMap<&str, Vec<&ShapePoint>> = HashMap::new();
+        for point in &self.shapes {
+            shapes
+                .entry(point.shape_id.as_str())
+                .or_default()
+                .push(point);
+        }
+        for points in shapes.values_mut() {
+            points.sort_by_key(|p| p.shape_pt_sequence);
+        }
+        shapes
+    }
+
+    /// Points of the shape `shape_id` ordered by shape_pt_sequence
+    pub fn shape(&self, shape_id: &str) -> Vec<&ShapePoint> {
+        let mut points: Vec<&ShapePoint> = self
+            .shapes
+            .iter()
+            .filter(|p| p.shape_id == shape_id)
+            .collect();
+        points.sort_by_key(|p| p.shape_pt_sequence);
+        points
+    }
+}
+
 #[derive(Debug)]
 pub struct FeedReader<P>
 where
@@ -18,10 +73,30 @@ where
 {
     provider: P,
     builder: csv::ReaderBuilder,
+    /// Whether `builder` was handed out, so the csv settings may differ
+    /// from the defaults
+    custom_builder: bool,
 }
 
+/// Locates a feed's files and opens them for reading
 pub trait FeedProvider {
+    type Reader: std::io::Read;
+
     fn path(&self) -> &str;
+
+    /// Opens the file at `path`, a file name joined onto `path()`
+    fn open(&self, path: &str) -> Result<Self::Reader, Error>;
+
+    /// The contents of the file at `path` when the provider holds them in
+    /// memory already, as memory maps do, so they can be read without a
+    /// copy
+    fn contents(&self, _path: &str) -> Result<Option<Box<dyn Deref<Target = [u8]>>>, Error> {
+        Ok(None)
+    }
+}
+
+fn open_file(path: &str) -> Result<File, Error> {
+    File::open(path).map_err(|e| Error::Csv(path.to_string(), e.into()))
 }
 
 pub struct LocalFeedProvider {
@@ -37,9 +112,15 @@ impl LocalFeedProvider {
 }
 
 impl FeedProvider for LocalFeedProvider {
+    type Reader = File;
+
     fn path(&self) -> &str {
         &self.path
     }
+
+    fn open(&self, path: &str) -> Result<File, Error> {
+        open_file(path)
+    }
 }
 
 #[derive(Debug)]
@@ -62,9 +143,56 @@ impl ZipFeedProvider {
 }
 
 impl FeedProvider for ZipFeedProvider {
+    type Reader = File;
+
     fn path(&self) -> &str {
         self.dir.path().to_str().unwrap()
     }
+
+    fn open(&self, path: &str) -> Result<File, Error> {
+        open_file(path)
+    }
+}
+
+/// Reads the files of a local feed directory through memory maps instead of
+/// buffered `File` reads
+#[cfg(feature = "mmap")]
+pub struct MmapFeedProvider {
+    path: String,
+}
+
+#[cfg(feature = "mmap")]
+impl MmapFeedProvider {
+    fn new(path: &str) -> MmapFeedProvider {
+        MmapFeedProvider {
+            path: path.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl FeedProvider for MmapFeedProvider {
+    type Reader = std::io::Cursor<memmap2::Mmap>;
+
+    fn path(&self) -> &str {
+        &self.path
+    }
+
+    fn open(&self, path: &str) -> Result<Self::Reader, Error> {
+        Ok(std::io::Cursor::new(map_file(path)?))
+    }
+
+    fn contents(&self, path: &str) -> Result<Option<Box<dyn Deref<Target = [u8]>>>, Error> {
+        Ok(Some(Box::new(map_file(path)?)))
+    }
+}
+
+#[cfg(feature = "mmap")]
+fn map_file(path: &str) -> Result<memmap2::Mmap, Error> {
+    let file = open_file(path)?;
+    // Safety: the map is only read, the feed files must not be modified
+    // while the reader is in use
+    unsafe { memmap2::Mmap::map(&file) }.map_err(|e| Error::Csv(path.to_string(), e.into()))
 }
 
 impl FeedReader<LocalFeedProvider> {
@@ -73,6 +201,13 @@ impl FeedReader<LocalFeedProvider> {
     }
 }
 
+#[cfg(feature = "mmap")]
+impl FeedReader<MmapFeedProvider> {
+    pub fn from_mmap(path: &str) -> Self {
+        FeedReader::from_provider(MmapFeedProvider::new(path))
+    }
+}
+
 impl FeedReader<ZipFeedProvider> {
     pub fn from_zip(zipfile: &str) -> Result<Self, Error> {
         Ok(FeedReader::from_provider(ZipFeedProvider::new(zipfile)?))
@@ -84,83 +219,273 @@ impl<P: FeedProvider> FeedReader<P> {
         FeedReader {
             provider,
             builder: csv::ReaderBuilder::new(),
+            custom_builder: false,
         }
     }
 
     pub fn builder(&mut self) -> &mut csv::ReaderBuilder {
+        self.custom_builder = true;
         &mut self.builder
     }
 
-    pub fn agencies(&self) -> Result<GTFSIterator<File, Agency>, Error> {
+    pub fn agencies(&self) -> Result<GTFSIterator<P::Reader, Agency>, Error> {
         self.make_iterator("agency.txt")
     }
 
-    pub fn stops(&self) -> Result<GTFSIterator<File, Stop>, Error> {
+    pub fn stops(&self) -> Result<GTFSIterator<P::Reader, Stop>, Error> {
         self.make_iterator("stops.txt")
     }
 
-    pub fn routes(&self) -> Result<GTFSIterator<File, Route>, Error> {
+    pub fn routes(&self) -> Result<GTFSIterator<P::Reader, Route>, Error> {
         self.make_iterator("routes.txt")
     }
 
-    pub fn trips(&self) -> Result<GTFSIterator<File, Trip>, Error> {
+    pub fn trips(&self) -> Result<GTFSIterator<P::Reader, Trip>, Error> {
         self.make_iterator("trips.txt")
     }
 
-    pub fn stop_times(&self) -> Result<GTFSIterator<File, StopTime>, Error> {
+    pub fn stop_times(&self) -> Result<GTFSIterator<P::Reader, StopTime>, Error> {
         self.make_iterator("stop_times.txt")
     }
 
-    pub fn calendars(&self) -> Result<GTFSIterator<File, Calendar>, Error> {
+    /// Reads stop_times.txt into a reused buffer, see `StopTimeRef`
+    pub fn stop_time_records(&self) -> Result<GTFSRecordReader<P::Reader>, Error> {
+        let path = self.path("stop_times.txt")?;
+        let reader = self.builder.from_reader(self.provider.open(&path)?);
+        GTFSRecordReader::new(reader, &path)
+    }
+
+    pub fn calendars(&self) -> Result<GTFSIterator<P::Reader, Calendar>, Error> {
         self.make_iterator("calendar.txt")
     }
 
-    pub fn calendar_dates(&self) -> Result<GTFSIterator<File, CalendarDate>, Error> {
+    pub fn calendar_dates(&self) -> Result<GTFSIterator<P::Reader, CalendarDate>, Error> {
         self.make_iterator("calendar_dates.txt")
     }
 
-    pub fn fare_attributes(&self) -> Result<GTFSIterator<File, FareAttribute>, Error> {
+    pub fn fare_attributes(&self) -> Result<GTFSIterator<P::Reader, FareAttribute>, Error> {
         self.make_iterator("fare_attributes.txt")
     }
 
-    pub fn fare_rules(&self) -> Result<GTFSIterator<File, FareRule>, Error> {
+    pub fn fare_rules(&self) -> Result<GTFSIterator<P::Reader, FareRule>, Error> {
         self.make_iterator("fare_rules.txt")
     }
 
-    pub fn shapes(&self) -> Result<GTFSIterator<File, ShapePoint>, Error> {
+    pub fn shapes(&self) -> Result<GTFSIterator<P::Reader, ShapePoint>, Error> {
         self.make_iterator("shapes.txt")
     }
 
-    pub fn frequencies(&self) -> Result<GTFSIterator<File, Frequency>, Error> {
+    pub fn frequencies(&self) -> Result<GTFSIterator<P::Reader, Frequency>, Error> {
         self.make_iterator("frequencies.txt")
     }
 
-    pub fn transfers(&self) -> Result<GTFSIterator<File, Transfer>, Error> {
+    pub fn transfers(&self) -> Result<GTFSIterator<P::Reader, Transfer>, Error> {
         self.make_iterator("transfers.txt")
     }
 
-    pub fn feed_info(&self) -> Result<GTFSIterator<File, FeedInfo>, Error> {
+    pub fn feed_info(&self) -> Result<GTFSIterator<P::Reader, FeedInfo>, Error> {
         self.make_iterator("feed_info.txt")
     }
 
-    fn make_iterator<T>(&self, filename: &str) -> Result<GTFSIterator<File, T>, Error>
+    /// Reads every table of the feed into memory, one after another
+    pub fn load(&self) -> Result<Feed, Error> {
+        Ok(Feed {
+            agencies: self.load_table("agency.txt")?,
+            stops: self.load_table("stops.txt")?,
+            routes: self.load_table("routes.txt")?,
+            trips: self.load_table("trips.txt")?,
+            stop_times: self.load_table("stop_times.txt")?,
+            calendars: self.load_table("calendar.txt")?,
+            calendar_dates: self.load_table("calendar_dates.txt")?,
+            fare_attributes: self.load_table("fare_attributes.txt")?,
+            fare_rules: self.load_table("fare_rules.txt")?,
+            shapes: self.load_table("shapes.txt")?,
+            frequencies: self.load_table("frequencies.txt")?,
+            transfers: self.load_table("transfers.txt")?,
+            feed_info: self.load_table("feed_info.txt")?,
+        })
+    }
+
+    fn load_table<T>(&self, filename: &str) -> Result<Vec<T>, Error>
     where
         T: serde::de::DeserializeOwned,
     {
-        let path = match Path::new(&self.provider.path()).join(filename).to_str() {
-            Some(path_str) => path_str.to_string(),
-            None => {
-                return Err(Error::Feed(format!(
-                    "failed to construct path from {} and {}",
-                    self.provider.path(),
-                    filename
-                )))
+        if self.is_missing_optional(filename)? {
+            return Ok(Vec::new());
+        }
+        self.make_iterator(filename)?.collect()
+    }
+
+    fn is_missing_optional(&self, filename: &str) -> Result<bool, Error> {
+        let required = requirement(filename) == Some(Requirement::Required);
+        Ok(!required && !Path::new(&self.path(filename)?).exists())
+    }
+
+    /// Lists the known GTFS files present or missing in the feed, with row
+    /// counts and headers of those present, and any unrecognized files.
+    /// Files that cannot be read as CSV have the error on their entry.
+    pub fn manifest(&self) -> Result<Manifest, Error> {
+        let mut filenames = Vec::new();
+        let dir = fs::read_dir(self.provider.path()).map_err(|e| Error::Feed(format!("{}", e)))?;
+        for entry in dir {
+            let entry = entry.map_err(|e| Error::Feed(format!("{}", e)))?;
+            let is_file = entry
+                .file_type()
+                .map_err(|e| Error::Feed(format!("{}", e)))?
+                .is_file();
+            if let (true, Some(name)) = (is_file, entry.file_name().to_str()) {
+                filenames.push(name.to_string());
             }
-        };
-        let reader = match self.builder.from_path(&path) {
-            Ok(reader) => reader,
+        }
+        filenames.sort();
+
+        let mut entries = Vec::with_capacity(GTFS_FILES.len());
+        for &(filename, requirement) in GTFS_FILES {
+            let present = filenames.iter().any(|f| f == filename);
+            // locations.geojson is the only file that is not CSV
+            let scan = if present && filename.ends_with(".txt") {
+                Some(self.scan(filename))
+            } else {
+                None
+            };
+            let (row_count, headers, error) = match scan {
+                Some(Ok((row_count, headers))) => (Some(row_count), Some(headers), None),
+                Some(Err(e)) => (None, None, Some(format!("{}", e))),
+                None => (None, None, None),
+            };
+            entries.push(ManifestEntry {
+                filename: filename.to_string(),
+                requirement,
+                present,
+                row_count,
+                headers,
+                error,
+            });
+        }
+        let unrecognized = filenames
+            .into_iter()
+            .filter(|f| requirement(f).is_none())
+            .collect();
+
+        Ok(Manifest {
+            entries,
+            unrecognized,
+        })
+    }
+
+    fn scan(&self, filename: &str) -> Result<(u64, Vec<String>), Error> {
+        let path = self.path(filename)?;
+        let mut reader = self.builder.from_reader(self.provider.open(&path)?);
+        let headers = match reader.headers() {
+            Ok(headers) => headers.iter().map(|h| h.to_string()).collect(),
             Err(e) => return Err(Error::Csv(path, e)),
         };
+        let mut record = csv::ByteRecord::new();
+        let mut row_count = 0;
+        loop {
+            match reader.read_byte_record(&mut record) {
+                Ok(true) => row_count += 1,
+                Ok(false) => break,
+                Err(e) => return Err(Error::Csv(path, e)),
+            }
+        }
+        Ok((row_count, headers))
+    }
+
+    fn path(&self, filename: &str) -> Result<String, Error> {
+        match Path::new(&self.provider.path()).join(filename).to_str() {
+            Some(path_str) => Ok(path_str.to_string()),
+            None => Err(Error::Feed(format!(
+                "failed to construct path from {} and {}",
+                self.provider.path(),
+                filename
+            ))),
+        }
+    }
+
+    fn make_iterator<T>(&self, filename: &str) -> Result<GTFSIterator<P::Reader, T>, Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let path = self.path(filename)?;
+        let reader = self.builder.from_reader(self.provider.open(&path)?);
         GTFSIterator::new(reader, &path)
     }
 }
+
+#[cfg(feature = "rayon")]
+impl<P: FeedProvider + Sync> FeedReader<P> {
+    /// Reads every table of the feed into memory concurrently. stop_times.txt
+    /// and shapes.txt are also split into chunks that are parsed in parallel.
+    /// The result is the same as `load`, including which error is returned
+    /// when several tables fail. Chunks are split assuming the default csv
+    /// settings, so once `builder` has been used these files are parsed in
+    /// one piece.
+    pub fn par_load(&self) -> Result<Feed, Error> {
+        let mut agencies = Ok(Vec::new());
+        let mut stops = Ok(Vec::new());
+        let mut routes = Ok(Vec::new());
+        let mut trips = Ok(Vec::new());
+        let mut stop_times = Ok(Vec::new());
+        let mut calendars = Ok(Vec::new());
+        let mut calendar_dates = Ok(Vec::new());
+        let mut fare_attributes = Ok(Vec::new());
+        let mut fare_rules = Ok(Vec::new());
+        let mut shapes = Ok(Vec::new());
+        let mut frequencies = Ok(Vec::new());
+        let mut transfers = Ok(Vec::new());
+        let mut feed_info = Ok(Vec::new());
+        rayon::scope(|s| {
+            s.spawn(|_| agencies = self.load_table("agency.txt"));
+            s.spawn(|_| stops = self.load_table("stops.txt"));
+            s.spawn(|_| routes = self.load_table("routes.txt"));
+            s.spawn(|_| trips = self.load_table("trips.txt"));
+            s.spawn(|_| stop_times = self.load_table_chunked("stop_times.txt"));
+            s.spawn(|_| calendars = self.load_table("calendar.txt"));
+            s.spawn(|_| calendar_dates = self.load_table("calendar_dates.txt"));
+            s.spawn(|_| fare_attributes = self.load_table("fare_attributes.txt"));
+            s.spawn(|_| fare_rules = self.load_table("fare_rules.txt"));
+            s.spawn(|_| shapes = self.load_table_chunked("shapes.txt"));
+            s.spawn(|_| frequencies = self.load_table("frequencies.txt"));
+            s.spawn(|_| transfers = self.load_table("transfers.txt"));
+            s.spawn(|_| feed_info = self.load_table("feed_info.txt"));
+        });
+        Ok(Feed {
+            agencies: agencies?,
+            stops: stops?,
+            routes: routes?,
+            trips: trips?,
+            stop_times: stop_times?,
+            calendars: calendars?,
+            calendar_dates: calendar_dates?,
+            fare_attributes: fare_attributes?,
+            fare_rules: fare_rules?,
+            shapes: shapes?,
+            frequencies: frequencies?,
+            transfers: transfers?,
+            feed_info: feed_info?,
+        })
+    }
+
+    fn load_table_chunked<T>(&self, filename: &str) -> Result<Vec<T>, Error>
+    where
+        T: serde::de::DeserializeOwned + Send,
+    {
+        if self.custom_builder {
+            return self.load_table(filename);
+        }
+        if self.is_missing_optional(filename)? {
+            return Ok(Vec::new());
+        }
+        let path = self.path(filename)?;
+        if let Some(data) = self.provider.contents(&path)? {
+            return read_chunked(&self.builder, &data, &path);
+        }
+        let mut data = Vec::new();
+        self.provider
+            .open(&path)?
+            .read_to_end(&mut data)
+            .map_err(|e| Error::Csv(path.clone(), e.into()))?;
+        read_chunked(&self.builder, &data, &path)
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::{HashMap, HashSet};
+
+use super::prune;
+use crate::feed::Feed;
+use crate::LocationType;
+
+/// Area
+/// A region given by (lat, lon) points
+#[derive(Debug)]
+pub enum Area {
+    /// From the south west corner `min` to the north east corner `max`
+    BBox { min: (f64, f64), max: (f64, f64) },
+    /// The ring of a polygon, which need not repeat its first point
+    Polygon(Vec<(f64, f64)>),
+}
+
+impl Area {
+    pub fn contains(&self, point: (f64, f64)) -> bool {
+        match self {
+            Area::BBox { min, max } => {
+                point.0 >= min.0 && point.0 <= max.0 && point.1 >= min.1 && point.1 <= max.1
+            }
+            Area::Polygon(ring) => {
+                // Crossings of a ray going east from the point
+                let mut inside = false;
+                for (i, a) in ring.iter().enumerate() {
+                    let b = ring[(i + 1) % ring.len()];
+                    if (a.0 > point.0) != (b.0 > point.0) {
+                        let lon = a.1 + (point.0 - a.0) / (b.0 - a.0) * (b.1 - a.1);
+                        if point.1 < lon {
+                            inside = !inside;
+                        }
+                    }
+                }
+                inside
+            }
+        }
+    }
+}
+
+/// What to keep of trips serving stops inside an `Area`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TripClipping {
+    /// Every stop time of trips stopping inside at least once
+    Whole,
+    /// Only the stop times inside, for trips stopping inside at least twice
+    Truncate,
+}
+
+/// Keeps the trips of `feed` serving stops inside `area`, clipped as
+/// `clipping` says, then removes what is no longer used. Stations count as
+/// inside when one of their stops is.
+pub fn filter_area(feed: &mut Feed, area: &Area, clipping: TripClipping) {
+    let mut inside: HashSet<String> = feed
+        .stops
+        .iter()
+        .filter(|s| s.location_type == LocationType::Stop)
+        .filter(|s| area.contains((s.stop_lat, s.stop_lon)))
+        .map(|s| s.stop_id.clone())
+        .collect();
+    let stations: HashSet<&str> = feed
+        .stops
+        .iter()
+        .filter(|s| s.location_type == LocationType::Station)
+        .filter(|s| area.contains((s.stop_lat, s.stop_lon)))
+        .map(|s| s.stop_id.as_str())
+        .collect();
+    for stop in &feed.stops {
+        if stop
+            .parent_station
+            .as_ref()
+            .is_some_and(|p| stations.contains(p.as_str()))
+        {
+            inside.insert(stop.stop_id.clone());
+        }
+    }
+
+    let mut served: HashMap<&str, usize> = HashMap::new();
+    for stop_time in &feed.stop_times {
+        if inside.contains(&stop_time.stop_id) {
+            *served.entry(stop_time.trip_id.as_str()).or_default() += 1;
+        }
+    }
+    let needed = match clipping {
+        TripClipping::Whole => 1,
+        TripClipping::Truncate => 2,
+    };
+    let kept: HashSet<String> = served
+        .into_iter()
+        .filter(|(_, count)| *count >= needed)
+        .map(|(trip_id, _)| trip_id.to_string())
+        .collect();
+    feed.trips.retain(|t| kept.contains(&t.trip_id));
+    if clipping == TripClipping::Truncate {
+        feed.stop_times.retain(|st| inside.contains(&st.stop_id));
+    }
+    prune(feed);
+}
@@ -0,0 +1,39 @@
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+use chrono::NaiveDate;
+
+use super::prune;
+use crate::feed::Feed;
+use crate::timetable::ServiceCalendar;
+
+/// Keeps the service of `feed` running on the service days in `dates`.
+/// Calendars are clipped to the window, calendar dates outside it dropped,
+/// trips whose service no longer runs removed along with what they alone
+/// used, and the dates of feed_info.txt narrowed to the window.
+pub fn filter_dates(feed: &mut Feed, dates: RangeInclusive<NaiveDate>) {
+    let (start, end) = (*dates.start(), *dates.end());
+    let running: HashSet<String> = {
+        let calendar = ServiceCalendar::new(feed);
+        start
+            .iter_days()
+            .take_while(|date| *date <= end)
+            .flat_map(|date| calendar.active_services(date))
+            .map(str::to_string)
+            .collect()
+    };
+
+    for calendar in feed.calendars.iter_mut() {
+        calendar.start_date = calendar.start_date.max(start);
+        calendar.end_date = calendar.end_date.min(end);
+    }
+    feed.calendars.retain(|c| c.start_date <= c.end_date);
+    feed.calendar_dates.retain(|d| dates.contains(&d.date));
+    feed.trips.retain(|t| running.contains(&t.service_id));
+    prune(feed);
+
+    for info in feed.feed_info.iter_mut() {
+        info.feed_start_date = Some(info.feed_start_date.map_or(start, |d| d.max(start)));
+        info.feed_end_date = Some(info.feed_end_date.map_or(end, |d| d.min(end)));
+    }
+}
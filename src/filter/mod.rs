@@ -0,0 +1,100 @@
+//! Transformations keeping part of a feed. Each one removes whatever the
+//! records it drops leave unused, so the result is referentially valid.
+mod area;
+mod dates;
+mod routes;
+
+pub use self::area::{filter_area, Area, TripClipping};
+pub use self::dates::filter_dates;
+pub use self::routes::{filter_routes, RouteSelection};
+
+use std::collections::HashSet;
+
+use crate::feed::Feed;
+
+/// Removes the records of `feed` no longer used by its routes and trips:
+/// trips without a route or stop times, routes without trips and what only
+/// they referenced. Stops are kept while stop times or a kept child stop
+/// use them.
+fn prune(feed: &mut Feed) {
+    let route_ids: HashSet<&str> = feed.routes.iter().map(|r| r.route_id.as_str()).collect();
+    feed.trips
+        .retain(|t| route_ids.contains(t.route_id.as_str()));
+    let trip_ids: HashSet<&str> = feed.trips.iter().map(|t| t.trip_id.as_str()).collect();
+    feed.stop_times
+        .retain(|st| trip_ids.contains(st.trip_id.as_str()));
+    let scheduled: HashSet<&str> = feed
+        .stop_times
+        .iter()
+        .map(|st| st.trip_id.as_str())
+        .collect();
+    feed.trips
+        .retain(|t| scheduled.contains(t.trip_id.as_str()));
+    feed.frequencies
+        .retain(|f| scheduled.contains(f.trip_id.as_str()));
+
+    let routed: HashSet<&str> = feed.trips.iter().map(|t| t.route_id.as_str()).collect();
+    feed.routes.retain(|r| routed.contains(r.route_id.as_str()));
+    let agency_ids: HashSet<&str> = feed
+        .routes
+        .iter()
+        .filter_map(|r| r.agency_id.as_deref())
+        .collect();
+    // Routes without agency_id belong to the only agency of the feed
+    feed.agencies.retain(|a| {
+        a.agency_id
+            .as_deref()
+            .is_none_or(|id| agency_ids.contains(id))
+    });
+
+    let shape_ids: HashSet<&str> = feed
+        .trips
+        .iter()
+        .filter_map(|t| t.shape_id.as_deref())
+        .collect();
+    feed.shapes
+        .retain(|p| shape_ids.contains(p.shape_id.as_str()));
+    let service_ids: HashSet<&str> = feed.trips.iter().map(|t| t.service_id.as_str()).collect();
+    feed.calendars
+        .retain(|c| service_ids.contains(c.service_id.as_str()));
+    feed.calendar_dates
+        .retain(|d| service_ids.contains(d.service_id.as_str()));
+
+    let mut stop_ids: HashSet<String> = feed
+        .stop_times
+        .iter()
+        .map(|st| st.stop_id.clone())
+        .collect();
+    let parents: Vec<String> = feed
+        .stops
+        .iter()
+        .filter(|s| stop_ids.contains(&s.stop_id))
+        .filter_map(|s| s.parent_station.clone())
+        .collect();
+    stop_ids.extend(parents);
+    feed.stops.retain(|s| stop_ids.contains(&s.stop_id));
+    let stop_ids: HashSet<&str> = feed.stops.iter().map(|s| s.stop_id.as_str()).collect();
+    feed.transfers.retain(|t| {
+        stop_ids.contains(t.from_stop_id.as_str()) && stop_ids.contains(t.to_stop_id.as_str())
+    });
+
+    let route_ids: HashSet<&str> = feed.routes.iter().map(|r| r.route_id.as_str()).collect();
+    let zone_ids: HashSet<&str> = feed
+        .stops
+        .iter()
+        .filter_map(|s| s.zone_id.as_deref())
+        .collect();
+    let ruled: HashSet<String> = feed.fare_rules.iter().map(|r| r.fare_id.clone()).collect();
+    feed.fare_rules.retain(|r| {
+        r.route_id
+            .as_deref()
+            .is_none_or(|id| route_ids.contains(id))
+            && [&r.origin_id, &r.destination_id, &r.contains_id]
+                .iter()
+                .all(|zone| zone.as_deref().is_none_or(|id| zone_ids.contains(id)))
+    });
+    // Fares without rules apply to the whole feed and are kept
+    let fare_ids: HashSet<&str> = feed.fare_rules.iter().map(|r| r.fare_id.as_str()).collect();
+    feed.fare_attributes
+        .retain(|f| fare_ids.contains(f.fare_id.as_str()) || !ruled.contains(&f.fare_id));
+}
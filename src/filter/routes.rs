@@ -0,0 +1,50 @@
+use std::collections::HashSet;
+
+use super::prune;
+use crate::feed::Feed;
+use crate::RouteType;
+
+/// RouteSelection
+/// Routes for `filter_routes` to keep, those matching any of the lists
+#[derive(Debug, Default)]
+pub struct RouteSelection {
+    pub agency_ids: Vec<String>,
+    pub route_ids: Vec<String>,
+    pub route_types: Vec<RouteType>,
+}
+
+impl RouteSelection {
+    pub fn agency(mut self, agency_id: &str) -> Self {
+        self.agency_ids.push(agency_id.to_string());
+        self
+    }
+
+    pub fn route(mut self, route_id: &str) -> Self {
+        self.route_ids.push(route_id.to_string());
+        self
+    }
+
+    pub fn route_type(mut self, route_type: RouteType) -> Self {
+        self.route_types.push(route_type);
+        self
+    }
+}
+
+/// Keeps the routes of `feed` in `selection` with their trips, and the
+/// stops, parent stations, shapes, calendars and fares those use
+pub fn filter_routes(feed: &mut Feed, selection: &RouteSelection) {
+    // Routes without agency_id belong to the only agency of the feed
+    let only_agency = match feed.agencies.as_slice() {
+        [agency] => agency.agency_id.clone(),
+        _ => None,
+    };
+    let agency_ids: HashSet<&str> = selection.agency_ids.iter().map(String::as_str).collect();
+    let route_ids: HashSet<&str> = selection.route_ids.iter().map(String::as_str).collect();
+    feed.routes.retain(|r| {
+        let agency_id = r.agency_id.as_deref().or(only_agency.as_deref());
+        agency_id.is_some_and(|id| agency_ids.contains(id))
+            || route_ids.contains(r.route_id.as_str())
+            || selection.route_types.contains(&r.route_type)
+    });
+    prune(feed);
+}
@@ -0,0 +1,194 @@
+//! GeoJSON (RFC 7946) output. Positions are (lat, lon) pairs as elsewhere in
+//! the crate and are written in GeoJSON's [lon, lat] order.
+use std::collections::{HashMap, HashSet};
+
+use serde_json::{json, Value};
+
+use crate::feed::Feed;
+use crate::{LocationType, RouteType, ShapePoint, WheelchairAccessible};
+
+fn coordinates(position: (f64, f64)) -> Value {
+    json!([position.1, position.0])
+}
+
+pub fn point(position: (f64, f64)) -> Value {
+    json!({ "type": "Point", "coordinates": coordinates(position) })
+}
+
+pub fn multi_point(positions: &[(f64, f64)]) -> Value {
+    let coordinates: Vec<Value> = positions.iter().copied().map(coordinates).collect();
+    json!({ "type": "MultiPoint", "coordinates": coordinates })
+}
+
+pub fn line_string(positions: &[(f64, f64)]) -> Value {
+    let coordinates: Vec<Value> = positions.iter().copied().map(coordinates).collect();
+    json!({ "type": "LineString", "coordinates": coordinates })
+}
+
+pub fn multi_line_string(lines: &[Vec<(f64, f64)>]) -> Value {
+    let coordinates: Vec<Value> = lines
+        .iter()
+        .map(|line| line.iter().copied().map(coordinates).collect())
+        .collect();
+    json!({ "type": "MultiLineString", "coordinates": coordinates })
+}
+
+/// A polygon with `ring` as its exterior, closed by repeating its first
+/// position
+pub fn polygon(ring: &[(f64, f64)]) -> Value {
+    let mut ring: Vec<Value> = ring.iter().copied().map(coordinates).collect();
+    if let Some(first) = ring.first().cloned() {
+        ring.push(first);
+    }
+    json!({ "type": "Polygon", "coordinates": [ring] })
+}
+
+/// `geometry` may be null for features without a location
+pub fn feature(geometry: Value, properties: Value) -> Value {
+    json!({ "type": "Feature", "geometry": geometry, "properties": properties })
+}
+
+pub fn feature_collection(features: Vec<Value>) -> String {
+    json!({ "type": "FeatureCollection", "features": features }).to_string()
+}
+
+fn location_type(location_type: &LocationType) -> u32 {
+    match location_type {
+        LocationType::Stop => 0,
+        LocationType::Station => 1,
+    }
+}
+
+fn wheelchair_boarding(wheelchair_boarding: &WheelchairAccessible) -> u32 {
+    match wheelchair_boarding {
+        WheelchairAccessible::NoInformation => 0,
+        WheelchairAccessible::SomeAccessibility => 1,
+        WheelchairAccessible::NoAccessibility => 2,
+    }
+}
+
+fn route_type(route_type: &RouteType) -> u32 {
+    match route_type {
+        RouteType::LightRail => 0,
+        RouteType::Subway => 1,
+        RouteType::Rail => 2,
+        RouteType::Bus => 3,
+        RouteType::Ferry => 4,
+        RouteType::CableCar => 5,
+        RouteType::Gondola => 6,
+        RouteType::Funicular => 7,
+    }
+}
+
+fn positions(points: &[&ShapePoint]) -> Vec<(f64, f64)> {
+    points
+        .iter()
+        .map(|p| (p.shape_pt_lat, p.shape_pt_lon))
+        .collect()
+}
+
+/// GeoJsonExporter
+/// Writes the stops, shapes and routes of a feed as GeoJSON
+/// FeatureCollections, with GTFS fields as properties under their GTFS names
+#[derive(Debug)]
+pub struct GeoJsonExporter<'a> {
+    feed: &'a Feed,
+}
+
+impl<'a> GeoJsonExporter<'a> {
+    pub fn new(feed: &'a Feed) -> Self {
+        GeoJsonExporter { feed }
+    }
+
+    /// A Point for each stop, in the order of stops.txt
+    pub fn stops(&self) -> String {
+        let features = self
+            .feed
+            .stops
+            .iter()
+            .map(|s| {
+                let properties = json!({
+                    "stop_id": s.stop_id,
+                    "stop_code": s.stop_code,
+                    "stop_name": s.stop_name,
+                    "stop_desc": s.stop_desc,
+                    "zone_id": s.zone_id,
+                    "stop_url": s.stop_url,
+                    "location_type": location_type(&s.location_type),
+                    "parent_station": s.parent_station,
+                    "wheelchair_boarding": wheelchair_boarding(&s.wheelchair_boarding),
+                });
+                feature(point((s.stop_lat, s.stop_lon)), properties)
+            })
+            .collect();
+        feature_collection(features)
+    }
+
+    /// A LineString for each shape with at least two points, ordered by
+    /// shape_id
+    pub fn shapes(&self) -> String {
+        let mut shapes: Vec<_> = self.feed.shapes_by_id().into_iter().collect();
+        shapes.sort_by_key(|(shape_id, _)| *shape_id);
+        let features = shapes
+            .into_iter()
+            .filter(|(_, points)| points.len() > 1)
+            .map(|(shape_id, points)| {
+                let properties = json!({ "shape_id": shape_id });
+                feature(line_string(&positions(&points)), properties)
+            })
+            .collect();
+        feature_collection(features)
+    }
+
+    /// A MultiLineString for each route with a line for each distinct shape
+    /// of its trips, in the order of routes.txt. The shapes are kept as
+    /// separate lines rather than joined into one, as the two directions
+    /// and branches of a route don't form a single path. Routes whose trips
+    /// have no shapes get a null geometry.
+    pub fn routes(&self) -> String {
+        let shapes = self.feed.shapes_by_id();
+        let mut route_shapes: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut seen = HashSet::new();
+        for trip in &self.feed.trips {
+            if let Some(shape_id) = trip.shape_id.as_deref() {
+                if seen.insert((trip.route_id.as_str(), shape_id)) {
+                    route_shapes
+                        .entry(trip.route_id.as_str())
+                        .or_default()
+                        .push(shape_id);
+                }
+            }
+        }
+        let features = self
+            .feed
+            .routes
+            .iter()
+            .map(|r| {
+                let lines: Vec<Vec<(f64, f64)>> = route_shapes
+                    .get(r.route_id.as_str())
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|shape_id| shapes.get(shape_id))
+                    .filter(|points| points.len() > 1)
+                    .map(|points| positions(points))
+                    .collect();
+                let geometry = if lines.is_empty() {
+                    Value::Null
+                } else {
+                    multi_line_string(&lines)
+                };
+                let properties = json!({
+                    "route_id": r.route_id,
+                    "agency_id": r.agency_id,
+                    "route_short_name": r.route_short_name,
+                    "route_long_name": r.route_long_name,
+                    "route_type": route_type(&r.route_type),
+                    "route_color": r.route_color,
+                    "route_text_color": r.route_text_color,
+                });
+                feature(geometry, properties)
+            })
+            .collect();
+        feature_collection(features)
+    }
+}
@@ -0,0 +1,77 @@
+//! Conversions to `geo_types` geometries. Coordinates are (x, y) =
+//! (lon, lat) as usual in georust.
+use geo_types::{coord, Coord, LineString, Point, Rect};
+
+use crate::feed::Feed;
+use crate::{ShapePoint, Stop};
+
+impl From<&Stop> for Point<f64> {
+    fn from(stop: &Stop) -> Self {
+        Point::new(stop.stop_lon, stop.stop_lat)
+    }
+}
+
+impl From<&ShapePoint> for Coord<f64> {
+    fn from(point: &ShapePoint) -> Self {
+        coord! { x: point.shape_pt_lon, y: point.shape_pt_lat }
+    }
+}
+
+impl From<&ShapePoint> for Point<f64> {
+    fn from(point: &ShapePoint) -> Self {
+        Point::from(Coord::from(point))
+    }
+}
+
+/// The smallest rectangle around `coords`
+fn bounding_rect(coords: impl Iterator<Item = Coord<f64>>) -> Option<Rect<f64>> {
+    coords.fold(None, |rect, c| {
+        Some(match rect {
+            None => Rect::new(c, c),
+            Some(rect) => Rect::new(
+                coord! { x: rect.min().x.min(c.x), y: rect.min().y.min(c.y) },
+                coord! { x: rect.max().x.max(c.x), y: rect.max().y.max(c.y) },
+            ),
+        })
+    })
+}
+
+impl Feed {
+    /// The shape `shape_id` as a line through its points in order, `None`
+    /// when the feed has no such shape
+    pub fn shape_line_string(&self, shape_id: &str) -> Option<LineString<f64>> {
+        let points = self.shape(shape_id);
+        if points.is_empty() {
+            return None;
+        }
+        Some(points.into_iter().map(Coord::from).collect())
+    }
+
+    /// Every shape of the feed as a line, by shape_id
+    pub fn shape_line_strings(&self) -> Vec<(&str, LineString<f64>)> {
+        let mut shapes: Vec<_> = self
+            .shapes_by_id()
+            .into_iter()
+            .map(|(id, points)| (id, points.into_iter().map(Coord::from).collect()))
+            .collect();
+        shapes.sort_by(|a: &(&str, LineString<f64>), b| a.0.cmp(b.0));
+        shapes
+    }
+
+    /// The smallest rectangle around all stops
+    pub fn stops_bounding_rect(&self) -> Option<Rect<f64>> {
+        bounding_rect(self.stops.iter().map(|s| Point::from(s).0))
+    }
+
+    /// The smallest rectangle around all shape points
+    pub fn shapes_bounding_rect(&self) -> Option<Rect<f64>> {
+        bounding_rect(self.shapes.iter().map(Coord::from))
+    }
+
+    /// The smallest rectangle around all stops and shape points, `None`
+    /// for a feed without either
+    pub fn bounding_rect(&self) -> Option<Rect<f64>> {
+        let stops = self.stops.iter().map(|s| Point::from(s).0);
+        bounding_rect(stops.chain(self.shapes.iter().map(Coord::from)))
+    }
+}
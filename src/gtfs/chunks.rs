@@ -0,0 +1,214 @@
+use super::error::Error;
+use super::gtfs::wrap_errorkind;
+use csv::{ErrorKind, ReaderBuilder, StringRecord};
+use rayon::prelude::*;
+use std::io::Read;
+use std::ops::Range;
+
+/// Files smaller than this are parsed as a single chunk
+const MIN_CHUNK_SIZE: usize = 1 << 20;
+
+/// A run of whole records within a file and the line it starts on
+#[derive(Debug, PartialEq)]
+struct Chunk {
+    bytes: Range<usize>,
+    line: u64,
+}
+
+/// Parses the csv contents of the file at `path` in chunks on the rayon
+/// thread pool. Records are returned in file order and the first error in
+/// file order wins.
+pub fn read_chunked<T>(builder: &ReaderBuilder, data: &[u8], path: &str) -> Result<Vec<T>, Error>
+where
+    T: serde::de::DeserializeOwned + Send,
+{
+    let chunk_count = (data.len() / MIN_CHUNK_SIZE).clamp(1, rayon::current_num_threads());
+    let (header, chunks) = split_chunks(data, chunk_count);
+    let parsed: Vec<Result<Vec<T>, Error>> = chunks
+        .par_iter()
+        .map(|chunk| {
+            read_chunk(
+                builder,
+                header,
+                &data[chunk.bytes.clone()],
+                chunk.line,
+                path,
+            )
+        })
+        .collect();
+    let mut records = Vec::new();
+    for chunk in parsed {
+        records.extend(chunk?);
+    }
+    Ok(records)
+}
+
+/// Each chunk is parsed with the header prepended so field names and
+/// record lengths are checked as usual, then positions are shifted back to
+/// lines of the original file.
+fn read_chunk<T>(
+    builder: &ReaderBuilder,
+    header: &[u8],
+    chunk: &[u8],
+    line: u64,
+    path: &str,
+) -> Result<Vec<T>, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    // Line 1 of the chunk reader is the header, line 2 is `line` of the file
+    let shift = |pos: &mut csv::Position| {
+        let line = pos.line() + line - 2;
+        pos.set_line(line);
+    };
+    let mut reader = builder.from_reader(header.chain(chunk));
+    let headers = match reader.headers() {
+        Ok(r) => r.clone(),
+        Err(e) => return Err(Error::Csv(path.to_string(), e)),
+    };
+    let mut record = StringRecord::new();
+    let mut records = Vec::new();
+    loop {
+        match reader.read_record(&mut record) {
+            Ok(true) => {
+                if let Some(mut pos) = record.position().cloned() {
+                    shift(&mut pos);
+                    record.set_position(Some(pos));
+                }
+                match record.deserialize(Some(&headers)) {
+                    Ok(r) => records.push(r),
+                    Err(e) => return Err(wrap_errorkind(e.into_kind(), &headers, path)),
+                }
+            }
+            Ok(false) => return Ok(records),
+            Err(e) => {
+                let mut kind = e.into_kind();
+                match kind {
+                    ErrorKind::UnequalLengths {
+                        pos: Some(ref mut pos),
+                        ..
+                    }
+                    | ErrorKind::Utf8 {
+                        pos: Some(ref mut pos),
+                        ..
+                    } => shift(pos),
+                    _ => (),
+                }
+                return Err(wrap_errorkind(kind, &headers, path));
+            }
+        }
+    }
+}
+
+/// Splits `data` into its header line and up to `count` chunks of roughly
+/// equal size, breaking only on line ends outside of quoted fields.
+fn split_chunks(data: &[u8], count: usize) -> (&[u8], Vec<Chunk>) {
+    let target = data.len() / count.max(1) + 1;
+    let mut header_end = None;
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut start_line = 2;
+    let mut line = 1;
+    let mut quoted = false;
+    for (i, &b) in data.iter().enumerate() {
+        match b {
+            b'"' => quoted = !quoted,
+            b'\n' if !quoted => {
+                line += 1;
+                match header_end {
+                    None => {
+                        header_end = Some(i + 1);
+                        start = i + 1;
+                    }
+                    Some(_) if i + 1 - start >= target => {
+                        chunks.push(Chunk {
+                            bytes: start..i + 1,
+                            line: start_line,
+                        });
+                        start = i + 1;
+                        start_line = line;
+                    }
+                    Some(_) => (),
+                }
+            }
+            b'\n' => line += 1,
+            _ => (),
+        }
+    }
+    let header_end = match header_end {
+        Some(end) => end,
+        None => return (data, chunks),
+    };
+    if start < data.len() {
+        chunks.push(Chunk {
+            bytes: start..data.len(),
+            line: start_line,
+        });
+    }
+    (&data[..header_end], chunks)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Test {
+        foo: String,
+        bar: f64,
+    }
+
+    #[test]
+    fn test_split_chunks_on_unquoted_lines() {
+        let data = b"foo,bar\nA,1\n\"B\nB\",2\nC,3\nD,4";
+        let (header, chunks) = split_chunks(data, 100);
+        assert_eq!(b"foo,bar\n", header);
+        assert_eq!(
+            vec![
+                Chunk {
+                    bytes: 8..12,
+                    line: 2
+                },
+                Chunk {
+                    bytes: 12..20,
+                    line: 3
+                },
+                Chunk {
+                    bytes: 20..24,
+                    line: 5
+                },
+                Chunk {
+                    bytes: 24..27,
+                    line: 6
+                },
+            ],
+            chunks
+        );
+    }
+
+    #[test]
+    fn test_split_chunks_header_only() {
+        let (header, chunks) = split_chunks(b"foo,bar", 4);
+        assert_eq!(b"foo,bar", header);
+        assert!(chunks.is_empty());
+    }
+
+    #[test]
+    fn test_error_lines_in_later_chunks() {
+        let data = b"foo,bar\nA,1\nB,2\nC,w\n";
+        let (header, chunks) = split_chunks(data, 100);
+        let last = chunks.last().unwrap();
+        let result: Result<Vec<Test>, Error> = read_chunk(
+            &ReaderBuilder::new(),
+            header,
+            &data[last.bytes.clone()],
+            last.line,
+            "test.txt",
+        );
+        assert_eq!(
+            "error parsing bar in test.txt:4 - invalid float literal",
+            format!("{}", result.err().unwrap())
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use super::error::Error;
 use csv::{
-    DeserializeError, DeserializeRecordsIntoIter, ErrorKind, Position, Reader, StringRecord,
+    DeserializeError, DeserializeRecordsIntoIter, Error as CsvError, ErrorKind, Position, Reader,
+    StringRecord,
 };
 
 pub struct GTFSIterator<R, T>
@@ -42,25 +43,41 @@ where
             filename: filename.to_string(),
         })
     }
+}
 
-    fn wrap_fielderror(&self, err: &DeserializeError, position: &Option<Position>) -> Error {
-        let fieldname = err
-            .field()
-            .map(|field_pos| match self.headers.get(field_pos as usize) {
-                Some(field) => field.to_string(),
-                None => format!("field {}", field_pos),
-            });
-
-        // TODO:: What if position.line() is None?
-        Error::FieldError(
-            String::clone(&self.filename),
-            position.as_ref().unwrap().line(),
-            err.kind().clone(),
-            fieldname,
-        )
+fn wrap_csverror(err: CsvError, headers: &StringRecord, filename: &str) -> Error {
+    wrap_errorkind(err.into_kind(), headers, filename)
+}
+
+pub(super) fn wrap_errorkind(kind: ErrorKind, headers: &StringRecord, filename: &str) -> Error {
+    match kind {
+        ErrorKind::Deserialize { ref pos, ref err } => wrap_fielderror(err, pos, headers, filename),
+        k => Error::LineError(filename.to_string(), k),
     }
 }
 
+fn wrap_fielderror(
+    err: &DeserializeError,
+    position: &Option<Position>,
+    headers: &StringRecord,
+    filename: &str,
+) -> Error {
+    let fieldname = err
+        .field()
+        .map(|field_pos| match headers.get(field_pos as usize) {
+            Some(field) => field.to_string(),
+            None => format!("field {}", field_pos),
+        });
+
+    // TODO:: What if position.line() is None?
+    Error::FieldError(
+        filename.to_string(),
+        position.as_ref().unwrap().line(),
+        err.kind().clone(),
+        fieldname,
+    )
+}
+
 impl<R, T> Iterator for GTFSIterator<R, T>
 where
     R: std::io::Read,
@@ -71,12 +88,7 @@ where
     fn next(&mut self) -> Option<Result<T, Error>> {
         match self.iter.next() {
             Some(r) => Some(match r {
-                Err(e) => match e.into_kind() {
-                    ErrorKind::Deserialize { ref pos, ref err } => {
-                        Err(self.wrap_fielderror(err, pos))
-                    }
-                    k => Err(Error::LineError(String::clone(&self.filename), k)),
-                },
+                Err(e) => Err(wrap_csverror(e, &self.headers, &self.filename)),
                 Ok(s) => Ok(s),
             }),
             None => None,
@@ -84,6 +96,63 @@ where
     }
 }
 
+/// Reads records into a single reused buffer and deserializes them into
+/// types that may borrow from it, such as `StopTimeRef`. Unlike
+/// `GTFSIterator` this cannot implement `Iterator` as each record only
+/// lives until the next call to `read`.
+pub struct GTFSRecordReader<R>
+where
+    R: std::io::Read,
+{
+    reader: Reader<R>,
+    record: StringRecord,
+    headers: StringRecord,
+    filename: String,
+}
+
+impl GTFSRecordReader<std::fs::File> {
+    pub fn from_path(filename: &str) -> Result<GTFSRecordReader<std::fs::File>, Error> {
+        let csv = match Reader::from_path(filename) {
+            Ok(c) => c,
+            Err(e) => return Err(Error::Csv(filename.to_string(), e)),
+        };
+        GTFSRecordReader::new(csv, filename)
+    }
+}
+
+impl<R> GTFSRecordReader<R>
+where
+    R: std::io::Read,
+{
+    pub fn new(mut reader: Reader<R>, filename: &str) -> Result<GTFSRecordReader<R>, Error> {
+        let headers = match reader.headers() {
+            Ok(r) => r.clone(),
+            Err(e) => return Err(Error::Csv(filename.to_string(), e)),
+        };
+        Ok(GTFSRecordReader {
+            reader,
+            record: StringRecord::new(),
+            headers,
+            filename: filename.to_string(),
+        })
+    }
+
+    pub fn read<'a, T>(&'a mut self) -> Option<Result<T, Error>>
+    where
+        T: serde::de::Deserialize<'a>,
+    {
+        match self.reader.read_record(&mut self.record) {
+            Ok(true) => Some(
+                self.record
+                    .deserialize(Some(&self.headers))
+                    .map_err(|e| wrap_csverror(e, &self.headers, &self.filename)),
+            ),
+            Ok(false) => None,
+            Err(e) => Some(Err(wrap_csverror(e, &self.headers, &self.filename))),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -162,6 +231,38 @@ Foo,1
         assert_eq!(expected, format!("{}", result));
     }
 
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestRef<'a> {
+        foo: &'a str,
+        bar: f64,
+    }
+
+    #[test]
+    fn test_read_borrowed_records() {
+        let data = "\
+foo,bar
+Foo,1.0
+Baz,w
+";
+        let reader = csv::Reader::from_reader(data.as_bytes());
+        let mut records = GTFSRecordReader::new(reader, "test.txt").unwrap();
+
+        let first: TestRef = records.read().unwrap().unwrap();
+        assert_eq!(
+            TestRef {
+                foo: "Foo",
+                bar: 1.0
+            },
+            first
+        );
+        let second: Result<TestRef, Error> = records.read().unwrap();
+        assert_eq!(
+            "error parsing bar in test.txt:3 - invalid float literal",
+            format!("{}", second.err().unwrap())
+        );
+        assert!(records.read::<TestRef>().is_none());
+    }
+
     #[test]
     fn test_error_file_missing() {
         let result: Result<GTFSIterator<_, Test>, Error> =
@@ -1,7 +1,11 @@
+#[cfg(feature = "rayon")]
+mod chunks;
 mod error;
 #[macro_use]
 pub mod parse;
 mod gtfs;
 
+#[cfg(feature = "rayon")]
+pub(crate) use chunks::read_chunked;
 pub use error::Error;
-pub use gtfs::GTFSIterator;
+pub use gtfs::{GTFSIterator, GTFSRecordReader};
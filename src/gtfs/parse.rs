@@ -1,5 +1,44 @@
 use chrono::{Duration, NaiveDate};
+use serde::de::Visitor;
 use serde::Deserializer;
+use std::fmt;
+use std::marker::PhantomData;
+
+struct StrVisitor<T, F> {
+    parse: F,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T, F> Visitor<'de> for StrVisitor<T, F>
+where
+    F: FnOnce(&str) -> Result<T, String>,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<T, E>
+    where
+        E: serde::de::Error,
+    {
+        (self.parse)(v).map_err(E::custom)
+    }
+}
+
+/// Deserializes a field by handing its text to `parse` without first
+/// copying it into an owned `String`
+pub fn deserialize_str_with<'de, D, T, F>(deserializer: D, parse: F) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    F: FnOnce(&str) -> Result<T, String>,
+{
+    deserializer.deserialize_str(StrVisitor {
+        parse,
+        marker: PhantomData,
+    })
+}
 
 pub fn deserialize_dow_field<'de, D>(deserializer: D) -> Result<bool, D::Error>
 where
@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use super::feed::{FeedProvider, FeedReader};
+use super::{
+    BikesAllowed, Error, StopServiceType, StopTimeRef, TimeOffset, Timepoint, Trip,
+    WheelchairAccessible,
+};
+
+/// A compact index standing in for a GTFS ID string
+pub trait Idx: Copy + Eq + std::hash::Hash {
+    fn from_index(index: usize) -> Self;
+    fn index(self) -> usize;
+}
+
+macro_rules! define_idx {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(u32);
+
+        impl Idx for $name {
+            fn from_index(index: usize) -> Self {
+                $name(u32::try_from(index).expect("too many ids"))
+            }
+
+            fn index(self) -> usize {
+                self.0 as usize
+            }
+        }
+    };
+}
+
+define_idx!(
+    /// Index of an `agency_id`
+    AgencyIdx
+);
+define_idx!(
+    /// Index of a `stop_id`
+    StopIdx
+);
+define_idx!(
+    /// Index of a `route_id`
+    RouteIdx
+);
+define_idx!(
+    /// Index of a `trip_id`
+    TripIdx
+);
+define_idx!(
+    /// Index of a `service_id`
+    ServiceIdx
+);
+define_idx!(
+    /// Index of a `shape_id`
+    ShapeIdx
+);
+
+/// Interner
+/// Maps ID strings to indices of type `I` handed out in first seen order,
+/// and back again. The map and the list share each ID string.
+#[derive(Debug)]
+pub struct Interner<I> {
+    ids: Vec<Arc<str>>,
+    indices: HashMap<Arc<str>, I>,
+}
+
+impl<I: Idx> Default for Interner<I> {
+    fn default() -> Self {
+        Interner {
+            ids: Vec::new(),
+            indices: HashMap::new(),
+        }
+    }
+}
+
+impl<I: Idx> Interner<I> {
+    pub fn new() -> Self {
+        Interner::default()
+    }
+
+    /// Returns the index for `id`, allocating a new one if it hasn't been seen
+    pub fn intern(&mut self, id: &str) -> I {
+        if let Some(&idx) = self.indices.get(id) {
+            return idx;
+        }
+        let idx = I::from_index(self.ids.len());
+        let id: Arc<str> = id.into();
+        self.ids.push(id.clone());
+        self.indices.insert(id, idx);
+        idx
+    }
+
+    pub fn get(&self, id: &str) -> Option<I> {
+        self.indices.get(id).copied()
+    }
+
+    /// Returns the ID string for `idx`. Panics if `idx` came from another
+    /// interner and is out of range.
+    pub fn resolve(&self, idx: I) -> &str {
+        &self.ids[idx.index()]
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (I, &str)> {
+        self.ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (I::from_index(i), &**id))
+    }
+}
+
+/// Interners for each kind of ID in a feed
+#[derive(Debug, Default)]
+pub struct FeedIds {
+    pub agencies: Interner<AgencyIdx>,
+    pub stops: Interner<StopIdx>,
+    pub routes: Interner<RouteIdx>,
+    pub trips: Interner<TripIdx>,
+    pub services: Interner<ServiceIdx>,
+    pub shapes: Interner<ShapeIdx>,
+}
+
+/// A `Trip` with its IDs interned
+#[derive(Debug)]
+pub struct InternedTrip {
+    pub route: RouteIdx,
+    pub service: ServiceIdx,
+    pub trip: TripIdx,
+    pub trip_headsign: Option<String>,
+    pub trip_short_name: Option<String>,
+    pub direction_id: Option<String>,
+    pub block_id: Option<String>,
+    pub shape: Option<ShapeIdx>,
+    pub wheelchair_accessible: WheelchairAccessible,
+    pub bikes_allowed: BikesAllowed,
+}
+
+/// A `StopTime` with its IDs interned
+#[derive(Debug, PartialEq)]
+pub struct InternedStopTime {
+    pub trip: TripIdx,
+    pub arrival_time: TimeOffset,
+    pub departure_time: TimeOffset,
+    pub stop: StopIdx,
+    pub stop_sequence: u64,
+    pub stop_headsign: Option<String>,
+    pub pickup_type: StopServiceType,
+    pub dropoff_type: StopServiceType,
+    pub shape_dist_traveled: Option<f64>,
+    pub timepoint: Timepoint,
+}
+
+impl FeedIds {
+    pub fn new() -> Self {
+        FeedIds::default()
+    }
+
+    pub fn intern_trip(&mut self, trip: Trip) -> InternedTrip {
+        InternedTrip {
+            route: self.routes.intern(&trip.route_id),
+            service: self.services.intern(&trip.service_id),
+            trip: self.trips.intern(&trip.trip_id),
+            trip_headsign: trip.trip_headsign,
+            trip_short_name: trip.trip_short_name,
+            direction_id: trip.direction_id,
+            block_id: trip.block_id,
+            shape: trip.shape_id.as_deref().map(|id| self.shapes.intern(id)),
+            wheelchair_accessible: trip.wheelchair_accessible,
+            bikes_allowed: trip.bikes_allowed,
+        }
+    }
+
+    pub fn intern_stop_time(&mut self, stop_time: StopTimeRef) -> InternedStopTime {
+        InternedStopTime {
+            trip: self.trips.intern(stop_time.trip_id),
+            arrival_time: stop_time.arrival_time,
+            departure_time: stop_time.departure_time,
+            stop: self.stops.intern(stop_time.stop_id),
+            stop_sequence: stop_time.stop_sequence,
+            stop_headsign: stop_time.stop_headsign.map(|s| s.to_string()),
+            pickup_type: stop_time.pickup_type,
+            dropoff_type: stop_time.dropoff_type,
+            shape_dist_traveled: stop_time.shape_dist_traveled,
+            timepoint: stop_time.timepoint,
+        }
+    }
+}
+
+/// InternedFeed
+/// Trips and stop times of a feed held in memory with their IDs interned.
+/// Agencies, stops and routes are interned in the order of their own files
+/// so their indices follow file order.
+#[derive(Debug)]
+pub struct InternedFeed {
+    pub ids: FeedIds,
+    pub trips: Vec<InternedTrip>,
+    pub stop_times: Vec<InternedStopTime>,
+}
+
+impl InternedFeed {
+    pub fn load<P: FeedProvider>(feed: &FeedReader<P>) -> Result<InternedFeed, Error> {
+        let mut ids = FeedIds::new();
+        for agency in feed.agencies()? {
+            if let Some(agency_id) = agency?.agency_id {
+                ids.agencies.intern(&agency_id);
+            }
+        }
+        for stop in feed.stops()? {
+            ids.stops.intern(&stop?.stop_id);
+        }
+        for route in feed.routes()? {
+            ids.routes.intern(&route?.route_id);
+        }
+
+        let mut trips = Vec::new();
+        for trip in feed.trips()? {
+            trips.push(ids.intern_trip(trip?));
+        }
+
+        let mut stop_times = Vec::new();
+        let mut records = feed.stop_time_records()?;
+        while let Some(stop_time) = records.read() {
+            stop_times.push(ids.intern_stop_time(stop_time?));
+        }
+
+        Ok(InternedFeed {
+            ids,
+            trips,
+            stop_times,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_intern_and_resolve() {
+        let mut stops: Interner<StopIdx> = Interner::new();
+        let a = stops.intern("A");
+        let b = stops.intern("B");
+        assert_eq!(a, stops.intern("A"));
+        assert_ne!(a, b);
+        assert_eq!(2, stops.len());
+        assert_eq!("B", stops.resolve(b));
+        assert_eq!(Some(a), stops.get("A"));
+        assert_eq!(None, stops.get("C"));
+        assert_eq!(vec![(a, "A"), (b, "B")], stops.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_intern_many() {
+        let mut stops: Interner<StopIdx> = Interner::new();
+        let ids: Vec<StopIdx> = (0..1000).map(|i| stops.intern(&i.to_string())).collect();
+        assert_eq!(1000, stops.len());
+        for (i, idx) in ids.iter().enumerate() {
+            assert_eq!(i, idx.index());
+            assert_eq!(Some(*idx), stops.get(&i.to_string()));
+            assert_eq!(i.to_string(), stops.resolve(*idx));
+        }
+        assert_eq!(None, stops.get("1000"));
+    }
+}
@@ -2,10 +2,39 @@
 //! and API clients for obtaining usable transit related information
 //! such as routes, stop, trips, stop times, and more.
 mod archive;
+mod diff;
+mod distance;
 pub mod feed;
+pub mod filter;
+#[cfg(feature = "geojson")]
+mod geojson;
+#[cfg(feature = "geo")]
+mod geometry;
 mod gtfs;
+mod intern;
+mod manifest;
+mod merge;
+#[cfg(feature = "realtime")]
+pub mod realtime;
+pub mod routing;
+pub mod shapes;
+mod spatial;
+mod timetable;
 mod transit;
 
-pub use feed::{FeedReader, Terminator, Trim};
-pub use gtfs::{Error, GTFSIterator};
+pub use diff::{Change, ChangeKind, FeedDiff, FieldChange, ScheduleChange, Service};
+pub use feed::{Feed, FeedReader, Terminator, Trim};
+#[cfg(feature = "geo")]
+pub use geo_types;
+#[cfg(feature = "geojson")]
+pub use geojson::GeoJsonExporter;
+pub use gtfs::{Error, GTFSIterator, GTFSRecordReader};
+pub use intern::{
+    AgencyIdx, FeedIds, Idx, InternedFeed, InternedStopTime, InternedTrip, Interner, RouteIdx,
+    ServiceIdx, ShapeIdx, StopIdx, TripIdx,
+};
+pub use manifest::{Manifest, ManifestEntry, Requirement, GTFS_FILES};
+pub use merge::{Collision, FeedMerger};
+pub use spatial::{StopFilter, StopIndex};
+pub use timetable::{Departure, ServiceCalendar, Timetable};
 pub use transit::*;
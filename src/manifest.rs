@@ -0,0 +1,105 @@
+/// Whether the GTFS reference requires a file to be part of a feed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Requirement {
+    Required,
+    /// Required only under some conditions, e.g. calendar.txt and
+    /// calendar_dates.txt where at least one of the two must be present
+    ConditionallyRequired,
+    Optional,
+    /// Forbidden under some conditions, e.g. networks.txt when routes.txt
+    /// has a network_id column
+    ConditionallyForbidden,
+}
+
+/// Files defined by the GTFS static reference along with their requirement
+pub const GTFS_FILES: &[(&str, Requirement)] = &[
+    ("agency.txt", Requirement::Required),
+    ("stops.txt", Requirement::Required),
+    ("routes.txt", Requirement::Required),
+    ("trips.txt", Requirement::Required),
+    ("stop_times.txt", Requirement::Required),
+    ("calendar.txt", Requirement::ConditionallyRequired),
+    ("calendar_dates.txt", Requirement::ConditionallyRequired),
+    ("fare_attributes.txt", Requirement::Optional),
+    ("fare_rules.txt", Requirement::Optional),
+    ("timeframes.txt", Requirement::Optional),
+    ("rider_categories.txt", Requirement::Optional),
+    ("fare_media.txt", Requirement::Optional),
+    ("fare_products.txt", Requirement::Optional),
+    ("fare_leg_rules.txt", Requirement::Optional),
+    ("fare_leg_join_rules.txt", Requirement::Optional),
+    ("fare_transfer_rules.txt", Requirement::Optional),
+    ("areas.txt", Requirement::Optional),
+    ("stop_areas.txt", Requirement::Optional),
+    ("networks.txt", Requirement::ConditionallyForbidden),
+    ("route_networks.txt", Requirement::ConditionallyForbidden),
+    ("shapes.txt", Requirement::Optional),
+    ("frequencies.txt", Requirement::Optional),
+    ("transfers.txt", Requirement::Optional),
+    ("pathways.txt", Requirement::Optional),
+    ("levels.txt", Requirement::ConditionallyRequired),
+    ("location_groups.txt", Requirement::Optional),
+    ("location_group_stops.txt", Requirement::Optional),
+    ("locations.geojson", Requirement::Optional),
+    ("booking_rules.txt", Requirement::Optional),
+    ("feed_info.txt", Requirement::ConditionallyRequired),
+    ("translations.txt", Requirement::Optional),
+    ("attributions.txt", Requirement::Optional),
+];
+
+/// ManifestEntry
+/// Describes one known GTFS file and what was found for it in a feed
+#[derive(Debug, PartialEq)]
+pub struct ManifestEntry {
+    pub filename: String,
+    pub requirement: Requirement,
+    pub present: bool,
+    /// Number of records, not counting the header, if the file is present
+    /// and could be read as CSV
+    pub row_count: Option<u64>,
+    /// Column headers, if the file is present and could be read as CSV
+    pub headers: Option<Vec<String>>,
+    /// Why the file could not be read, if it is present but not valid CSV
+    pub error: Option<String>,
+}
+
+/// Manifest
+/// Lists every known GTFS file and whether it is present in a feed, as well
+/// as any files in the feed that are not part of GTFS
+#[derive(Debug, PartialEq)]
+pub struct Manifest {
+    pub entries: Vec<ManifestEntry>,
+    pub unrecognized: Vec<String>,
+}
+
+impl Manifest {
+    pub fn get(&self, filename: &str) -> Option<&ManifestEntry> {
+        self.entries.iter().find(|e| e.filename == filename)
+    }
+
+    pub fn is_present(&self, filename: &str) -> bool {
+        self.get(filename).is_some_and(|e| e.present)
+    }
+
+    pub fn present(&self) -> impl Iterator<Item = &ManifestEntry> {
+        self.entries.iter().filter(|e| e.present)
+    }
+
+    pub fn missing(&self) -> impl Iterator<Item = &ManifestEntry> {
+        self.entries.iter().filter(|e| !e.present)
+    }
+
+    /// Required files missing from the feed. Conditionally required files
+    /// are not included as their conditions depend on the feed contents.
+    pub fn missing_required(&self) -> impl Iterator<Item = &ManifestEntry> {
+        self.missing()
+            .filter(|e| e.requirement == Requirement::Required)
+    }
+}
+
+pub fn requirement(filename: &str) -> Option<Requirement> {
+    GTFS_FILES
+        .iter()
+        .find(|(name, _)| *name == filename)
+        .map(|(_, requirement)| *requirement)
+}
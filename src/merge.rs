@@ -0,0 +1,461 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::diff::Service;
+use crate::distance::haversine;
+use crate::feed::Feed;
+use crate::{Error, Frequency, StopTime, Trip};
+
+/// What `FeedMerger` does with IDs of an added feed already used in the
+/// merged feed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Collision {
+    /// Prefix every ID of the added feed, colliding or not
+    PrefixAll,
+    /// Prefix the colliding IDs
+    Prefix,
+    /// Merge entities identical to the one already using their ID and
+    /// prefix the others. Stops count as identical when within
+    /// `FeedMerger::stop_distance` of each other.
+    Identical,
+}
+
+/// New IDs for the entities of one kind of an added feed
+#[derive(Debug, Default)]
+struct Renames {
+    ids: HashMap<String, String>,
+    /// IDs of entities the merged feed already has
+    duplicates: HashSet<String>,
+}
+
+impl Renames {
+    fn apply(&self, id: &mut String) {
+        if let Some(new) = self.ids.get(id.as_str()) {
+            *id = new.clone();
+        }
+    }
+
+    fn apply_option(&self, id: &mut Option<String>) {
+        if let Some(id) = id {
+            self.apply(id);
+        }
+    }
+
+    fn is_duplicate(&self, id: &str) -> bool {
+        self.duplicates.contains(id)
+    }
+}
+
+/// FeedMerger
+/// Combines feeds, such as those of neighbouring agencies, into one
+#[derive(Debug)]
+pub struct FeedMerger {
+    feed: Feed,
+    pub collision: Collision,
+    /// Meters within which stops sharing a stop_id are the same stop
+    pub stop_distance: f64,
+}
+
+impl FeedMerger {
+    pub fn new(collision: Collision) -> Self {
+        FeedMerger {
+            feed: Feed::default(),
+            collision,
+            stop_distance: 10.0,
+        }
+    }
+
+    /// Merges `feed` in, renaming IDs by putting `prefix` in front as
+    /// `collision` says. Zones and blocks count as identical when they group
+    /// the same stop and trip IDs. Fails without changing the merged feed
+    /// when an ID needs renaming and `prefix` is empty.
+    pub fn add(&mut self, mut feed: Feed, prefix: &str) -> Result<(), Error> {
+        name_agency(&mut feed);
+        let merged = &self.feed;
+
+        let zones = {
+            let existing = members(merged.stops.iter().map(|s| (&s.zone_id, &s.stop_id)));
+            let added = members(feed.stops.iter().map(|s| (&s.zone_id, &s.stop_id)));
+            self.renames(
+                added.keys().copied(),
+                existing.keys().copied(),
+                prefix,
+                |id| existing[id] == added[id],
+            )?
+        };
+        let blocks = {
+            let existing = members(merged.trips.iter().map(|t| (&t.block_id, &t.trip_id)));
+            let added = members(feed.trips.iter().map(|t| (&t.block_id, &t.trip_id)));
+            self.renames(
+                added.keys().copied(),
+                existing.keys().copied(),
+                prefix,
+                |id| existing[id] == added[id],
+            )?
+        };
+        for stop in feed.stops.iter_mut() {
+            zones.apply_option(&mut stop.zone_id);
+        }
+        for rule in feed.fare_rules.iter_mut() {
+            zones.apply_option(&mut rule.origin_id);
+            zones.apply_option(&mut rule.destination_id);
+            zones.apply_option(&mut rule.contains_id);
+        }
+        for trip in feed.trips.iter_mut() {
+            blocks.apply_option(&mut trip.block_id);
+        }
+
+        let agencies = {
+            let existing: HashMap<&str, _> = merged
+                .agencies
+                .iter()
+                .filter_map(|a| Some((a.agency_id.as_deref()?, a)))
+                .collect();
+            let added: HashMap<&str, _> = feed
+                .agencies
+                .iter()
+                .filter_map(|a| Some((a.agency_id.as_deref()?, a)))
+                .collect();
+            self.renames(
+                added.keys().copied(),
+                existing.keys().copied(),
+                prefix,
+                |id| existing[id] == added[id],
+            )?
+        };
+        for agency in feed.agencies.iter_mut() {
+            agencies.apply_option(&mut agency.agency_id);
+        }
+        for route in feed.routes.iter_mut() {
+            agencies.apply_option(&mut route.agency_id);
+        }
+
+        let stops = {
+            let existing: HashMap<&str, _> = merged
+                .stops
+                .iter()
+                .map(|s| (s.stop_id.as_str(), (s.stop_lat, s.stop_lon)))
+                .collect();
+            let added: HashMap<&str, _> = feed
+                .stops
+                .iter()
+                .map(|s| (s.stop_id.as_str(), (s.stop_lat, s.stop_lon)))
+                .collect();
+            self.renames(
+                added.keys().copied(),
+                existing.keys().copied(),
+                prefix,
+                |id| haversine(existing[id], added[id]) <= self.stop_distance,
+            )?
+        };
+        for stop in feed.stops.iter_mut() {
+            stops.apply(&mut stop.stop_id);
+            stops.apply_option(&mut stop.parent_station);
+        }
+        for stop_time in feed.stop_times.iter_mut() {
+            stops.apply(&mut stop_time.stop_id);
+        }
+        for transfer in feed.transfers.iter_mut() {
+            stops.apply(&mut transfer.from_stop_id);
+            stops.apply(&mut transfer.to_stop_id);
+        }
+
+        let routes = {
+            let existing: HashMap<&str, _> = merged
+                .routes
+                .iter()
+                .map(|r| (r.route_id.as_str(), r))
+                .collect();
+            let added: HashMap<&str, _> = feed
+                .routes
+                .iter()
+                .map(|r| (r.route_id.as_str(), r))
+                .collect();
+            self.renames(
+                added.keys().copied(),
+                existing.keys().copied(),
+                prefix,
+                |id| existing[id] == added[id],
+            )?
+        };
+        for route in feed.routes.iter_mut() {
+            routes.apply(&mut route.route_id);
+        }
+        for trip in feed.trips.iter_mut() {
+            routes.apply(&mut trip.route_id);
+        }
+        for rule in feed.fare_rules.iter_mut() {
+            routes.apply_option(&mut rule.route_id);
+        }
+
+        let services = {
+            let existing = Service::by_id(merged);
+            let added = Service::by_id(&feed);
+            self.renames(
+                added.keys().copied(),
+                existing.keys().copied(),
+                prefix,
+                |id| existing[id] == added[id],
+            )?
+        };
+        for calendar in feed.calendars.iter_mut() {
+            services.apply(&mut calendar.service_id);
+        }
+        for date in feed.calendar_dates.iter_mut() {
+            services.apply(&mut date.service_id);
+        }
+        for trip in feed.trips.iter_mut() {
+            services.apply(&mut trip.service_id);
+        }
+
+        let shapes = {
+            let existing = merged.shapes_by_id();
+            let added = feed.shapes_by_id();
+            self.renames(
+                added.keys().copied(),
+                existing.keys().copied(),
+                prefix,
+                |id| existing[id] == added[id],
+            )?
+        };
+        for point in feed.shapes.iter_mut() {
+            shapes.apply(&mut point.shape_id);
+        }
+        for trip in feed.trips.iter_mut() {
+            shapes.apply_option(&mut trip.shape_id);
+        }
+
+        let trips = {
+            let existing = trips(merged);
+            let added = trips(&feed);
+            self.renames(
+                added.keys().copied(),
+                existing.keys().copied(),
+                prefix,
+                |id| existing[id] == added[id],
+            )?
+        };
+        for trip in feed.trips.iter_mut() {
+            trips.apply(&mut trip.trip_id);
+        }
+        for stop_time in feed.stop_times.iter_mut() {
+            trips.apply(&mut stop_time.trip_id);
+        }
+        for frequency in feed.frequencies.iter_mut() {
+            trips.apply(&mut frequency.trip_id);
+        }
+
+        let fares = {
+            let existing: HashMap<&str, _> = merged
+                .fare_attributes
+                .iter()
+                .map(|f| (f.fare_id.as_str(), f))
+                .collect();
+            let added: HashMap<&str, _> = feed
+                .fare_attributes
+                .iter()
+                .map(|f| (f.fare_id.as_str(), f))
+                .collect();
+            self.renames(
+                added.keys().copied(),
+                existing.keys().copied(),
+                prefix,
+                |id| existing[id] == added[id],
+            )?
+        };
+        for fare in feed.fare_attributes.iter_mut() {
+            fares.apply(&mut fare.fare_id);
+        }
+        for rule in feed.fare_rules.iter_mut() {
+            fares.apply(&mut rule.fare_id);
+        }
+
+        let merged = &mut self.feed;
+        feed.agencies.retain(|a| {
+            a.agency_id
+                .as_deref()
+                .is_none_or(|id| !agencies.is_duplicate(id))
+        });
+        feed.stops.retain(|s| !stops.is_duplicate(&s.stop_id));
+        feed.routes.retain(|r| !routes.is_duplicate(&r.route_id));
+        feed.calendars
+            .retain(|c| !services.is_duplicate(&c.service_id));
+        feed.calendar_dates
+            .retain(|d| !services.is_duplicate(&d.service_id));
+        feed.shapes.retain(|p| !shapes.is_duplicate(&p.shape_id));
+        feed.trips.retain(|t| !trips.is_duplicate(&t.trip_id));
+        feed.stop_times
+            .retain(|st| !trips.is_duplicate(&st.trip_id));
+        feed.frequencies.retain(|f| !trips.is_duplicate(&f.trip_id));
+        feed.fare_attributes
+            .retain(|f| !fares.is_duplicate(&f.fare_id));
+        let transfers: HashSet<_> = merged.transfers.iter().collect();
+        feed.transfers.retain(|t| !transfers.contains(t));
+        let fare_rules: HashSet<_> = merged.fare_rules.iter().collect();
+        feed.fare_rules.retain(|r| !fare_rules.contains(r));
+
+        merged.agencies.append(&mut feed.agencies);
+        merged.stops.append(&mut feed.stops);
+        merged.routes.append(&mut feed.routes);
+        merged.trips.append(&mut feed.trips);
+        merged.stop_times.append(&mut feed.stop_times);
+        merged.calendars.append(&mut feed.calendars);
+        merged.calendar_dates.append(&mut feed.calendar_dates);
+        merged.fare_attributes.append(&mut feed.fare_attributes);
+        merged.fare_rules.append(&mut feed.fare_rules);
+        merged.shapes.append(&mut feed.shapes);
+        merged.frequencies.append(&mut feed.frequencies);
+        merged.transfers.append(&mut feed.transfers);
+        merge_feed_info(merged, feed);
+        Ok(())
+    }
+
+    pub fn into_feed(self) -> Feed {
+        self.feed
+    }
+
+    /// New IDs for the `added` IDs given the `existing` ones, with
+    /// `identical` telling whether the entities named by a colliding ID are
+    /// the same
+    fn renames<'a>(
+        &self,
+        added: impl Iterator<Item = &'a str>,
+        existing: impl Iterator<Item = &'a str>,
+        prefix: &str,
+        identical: impl Fn(&str) -> bool,
+    ) -> Result<Renames, Error> {
+        let taken: HashSet<&str> = existing.collect();
+        let mut added: Vec<&str> = added.collect();
+        added.sort_unstable();
+        added.dedup();
+        let own: HashSet<&str> = added.iter().copied().collect();
+        let mut renames = Renames::default();
+        let mut used: HashSet<String> = HashSet::new();
+        for id in added {
+            let collides = taken.contains(id);
+            let rename = match self.collision {
+                Collision::PrefixAll => true,
+                Collision::Prefix => collides,
+                Collision::Identical if collides && identical(id) => {
+                    renames.duplicates.insert(id.to_string());
+                    false
+                }
+                Collision::Identical => collides,
+            };
+            let mut new = id.to_string();
+            if rename {
+                if prefix.is_empty() {
+                    return Err(Error::Feed(format!(
+                        "ID {} collides and there is no prefix to rename it with",
+                        id
+                    )));
+                }
+                // Clear of the merged IDs, the other added ones and those
+                // given so far
+                new = format!("{}{}", prefix, new);
+                while taken.contains(new.as_str())
+                    || own.contains(new.as_str())
+                    || used.contains(&new)
+                {
+                    new = format!("{}{}", prefix, new);
+                }
+                used.insert(new.clone());
+            }
+            renames.ids.insert(id.to_string(), new);
+        }
+        Ok(renames)
+    }
+}
+
+/// Gives the only agency of `feed` its name as agency_id when it has none,
+/// as a merged feed with several agencies needs them
+fn name_agency(feed: &mut Feed) {
+    let agency_id = match feed.agencies.as_mut_slice() {
+        [agency] if agency.agency_id.is_none() => {
+            agency.agency_id = Some(agency.agency_name.clone());
+            agency.agency_id.clone()
+        }
+        _ => return,
+    };
+    for route in feed.routes.iter_mut() {
+        if route.agency_id.is_none() {
+            route.agency_id = agency_id.clone();
+        }
+    }
+}
+
+/// The sorted IDs of the members of each group, such as the stops of zones
+fn members<'a>(
+    records: impl Iterator<Item = (&'a Option<String>, &'a String)>,
+) -> HashMap<&'a str, Vec<&'a str>> {
+    let mut groups: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (group, member) in records {
+        if let Some(group) = group {
+            groups.entry(group).or_default().push(member);
+        }
+    }
+    for members in groups.values_mut() {
+        members.sort_unstable();
+    }
+    groups
+}
+
+/// A trip as compared when merging, with its stop times in order and its
+/// frequencies
+#[derive(Debug, PartialEq)]
+struct TripContents<'a> {
+    trip: &'a Trip,
+    stop_times: Vec<&'a StopTime>,
+    frequencies: Vec<&'a Frequency>,
+}
+
+fn trips(feed: &Feed) -> HashMap<&str, TripContents<'_>> {
+    let mut trips: HashMap<&str, TripContents> = feed
+        .trips
+        .iter()
+        .map(|trip| {
+            let contents = TripContents {
+                trip,
+                stop_times: Vec::new(),
+                frequencies: Vec::new(),
+            };
+            (trip.trip_id.as_str(), contents)
+        })
+        .collect();
+    for stop_time in &feed.stop_times {
+        if let Some(trip) = trips.get_mut(stop_time.trip_id.as_str()) {
+            trip.stop_times.push(stop_time);
+        }
+    }
+    for frequency in &feed.frequencies {
+        if let Some(trip) = trips.get_mut(frequency.trip_id.as_str()) {
+            trip.frequencies.push(frequency);
+        }
+    }
+    for trip in trips.values_mut() {
+        trip.stop_times.sort_by_key(|st| st.stop_sequence);
+    }
+    trips
+}
+
+/// Widens the dates of the merged feed_info.txt to cover those of `feed`
+fn merge_feed_info(merged: &mut Feed, mut feed: Feed) {
+    let added = match feed.feed_info.pop() {
+        Some(info) => info,
+        None => return,
+    };
+    let info = match merged.feed_info.first_mut() {
+        Some(info) => info,
+        None => {
+            merged.feed_info.push(added);
+            return;
+        }
+    };
+    info.feed_start_date = match (info.feed_start_date, added.feed_start_date) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+    info.feed_end_date = match (info.feed_end_date, added.feed_end_date) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    };
+}
@@ -0,0 +1,761 @@
+//! Messages of gtfs-realtime.proto, laid out as prost-build would generate
+//! them. Nested messages and enums live in a module named after their
+//! parent message.
+//!
+//! Fields of the NYC Subway extension (nyct-subway.proto) are included
+//! under their extension tags as they are the most widely deployed.
+//! Other extensions are skipped when decoding.
+//!
+//! Serializing with serde follows the protobuf JSON mapping.
+
+/// The contents of a feed message
+#[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedMessage {
+    #[prost(message, required, tag = "1")]
+    pub header: FeedHeader,
+    #[prost(message, repeated, tag = "2")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub entity: Vec<FeedEntity>,
+}
+
+/// Metadata about a feed, included in feed messages
+#[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedHeader {
+    #[prost(string, required, tag = "1")]
+    pub gtfs_realtime_version: String,
+    #[prost(
+        enumeration = "feed_header::Incrementality",
+        optional,
+        tag = "2",
+        default = "FullDataset"
+    )]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::realtime::json::enumeration::<feed_header::Incrementality, _>"
+    )]
+    pub incrementality: Option<i32>,
+    #[prost(uint64, optional, tag = "3")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::realtime::json::int64"
+    )]
+    pub timestamp: Option<u64>,
+    #[prost(string, optional, tag = "4")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feed_version: Option<String>,
+    #[prost(message, optional, tag = "1001")]
+    #[serde(
+        rename = "[transit_realtime.nyct_feed_header]",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub nyct_feed_header: Option<NyctFeedHeader>,
+}
+
+pub mod feed_header {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Incrementality {
+        FullDataset = 0,
+        Differential = 1,
+    }
+}
+
+/// A definition or update of an entity in the transit feed
+#[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedEntity {
+    #[prost(string, required, tag = "1")]
+    pub id: String,
+    #[prost(bool, optional, tag = "2", default = "false")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_deleted: Option<bool>,
+    #[prost(message, optional, tag = "3")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trip_update: Option<TripUpdate>,
+    #[prost(message, optional, tag = "4")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vehicle: Option<VehiclePosition>,
+    #[prost(message, optional, tag = "5")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alert: Option<Alert>,
+}
+
+/// Realtime update of the progress of a vehicle along a trip
+#[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TripUpdate {
+    #[prost(message, required, tag = "1")]
+    pub trip: TripDescriptor,
+    #[prost(message, optional, tag = "3")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vehicle: Option<VehicleDescriptor>,
+    #[prost(message, repeated, tag = "2")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop_time_update: Vec<trip_update::StopTimeUpdate>,
+    #[prost(uint64, optional, tag = "4")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::realtime::json::int64"
+    )]
+    pub timestamp: Option<u64>,
+    #[prost(int32, optional, tag = "5")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delay: Option<i32>,
+    #[prost(message, optional, tag = "6")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trip_properties: Option<trip_update::TripProperties>,
+}
+
+pub mod trip_update {
+    /// Timing information for a single predicted event, arrival or departure
+    #[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StopTimeEvent {
+        #[prost(int32, optional, tag = "1")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub delay: Option<i32>,
+        #[prost(int64, optional, tag = "2")]
+        #[serde(
+            skip_serializing_if = "Option::is_none",
+            serialize_with = "crate::realtime::json::int64"
+        )]
+        pub time: Option<i64>,
+        #[prost(int32, optional, tag = "3")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub uncertainty: Option<i32>,
+        #[prost(int64, optional, tag = "4")]
+        #[serde(
+            skip_serializing_if = "Option::is_none",
+            serialize_with = "crate::realtime::json::int64"
+        )]
+        pub scheduled_time: Option<i64>,
+    }
+
+    /// Realtime update for arrival and/or departure events for a given stop
+    #[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StopTimeUpdate {
+        #[prost(uint32, optional, tag = "1")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub stop_sequence: Option<u32>,
+        #[prost(string, optional, tag = "4")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub stop_id: Option<String>,
+        #[prost(message, optional, tag = "2")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub arrival: Option<StopTimeEvent>,
+        #[prost(message, optional, tag = "3")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub departure: Option<StopTimeEvent>,
+        #[prost(
+            enumeration = "super::vehicle_position::OccupancyStatus",
+            optional,
+            tag = "7"
+        )]
+        #[serde(
+            skip_serializing_if = "Option::is_none",
+            serialize_with = "crate::realtime::json::enumeration::<super::vehicle_position::OccupancyStatus, _>"
+        )]
+        pub departure_occupancy_status: Option<i32>,
+        #[prost(
+            enumeration = "stop_time_update::ScheduleRelationship",
+            optional,
+            tag = "5",
+            default = "Scheduled"
+        )]
+        #[serde(
+            skip_serializing_if = "Option::is_none",
+            serialize_with = "crate::realtime::json::enumeration::<stop_time_update::ScheduleRelationship, _>"
+        )]
+        pub schedule_relationship: Option<i32>,
+        #[prost(message, optional, tag = "6")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub stop_time_properties: Option<stop_time_update::StopTimeProperties>,
+        #[prost(message, optional, tag = "1001")]
+        #[serde(
+            rename = "[transit_realtime.nyct_stop_time_update]",
+            skip_serializing_if = "Option::is_none"
+        )]
+        pub nyct_stop_time_update: Option<super::NyctStopTimeUpdate>,
+    }
+
+    pub mod stop_time_update {
+        /// Realtime updates for properties defined within GTFS stop_times.txt
+        #[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        pub struct StopTimeProperties {
+            #[prost(string, optional, tag = "1")]
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub assigned_stop_id: Option<String>,
+            #[prost(string, optional, tag = "2")]
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub stop_headsign: Option<String>,
+            #[prost(
+                enumeration = "stop_time_properties::DropOffPickupType",
+                optional,
+                tag = "3"
+            )]
+            #[serde(
+                skip_serializing_if = "Option::is_none",
+                serialize_with = "crate::realtime::json::enumeration::<stop_time_properties::DropOffPickupType, _>"
+            )]
+            pub pickup_type: Option<i32>,
+            #[prost(
+                enumeration = "stop_time_properties::DropOffPickupType",
+                optional,
+                tag = "4"
+            )]
+            #[serde(
+                skip_serializing_if = "Option::is_none",
+                serialize_with = "crate::realtime::json::enumeration::<stop_time_properties::DropOffPickupType, _>"
+            )]
+            pub drop_off_type: Option<i32>,
+        }
+
+        pub mod stop_time_properties {
+            #[derive(
+                Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration,
+            )]
+            #[repr(i32)]
+            pub enum DropOffPickupType {
+                Regular = 0,
+                None = 1,
+                PhoneAgency = 2,
+                CoordinateWithDriver = 3,
+            }
+        }
+
+        #[derive(
+            Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration,
+        )]
+        #[repr(i32)]
+        pub enum ScheduleRelationship {
+            Scheduled = 0,
+            Skipped = 1,
+            NoData = 2,
+            Unscheduled = 3,
+        }
+    }
+
+    /// Updated properties of the trip
+    #[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct TripProperties {
+        #[prost(string, optional, tag = "1")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub trip_id: Option<String>,
+        #[prost(string, optional, tag = "2")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub start_date: Option<String>,
+        #[prost(string, optional, tag = "3")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub start_time: Option<String>,
+        #[prost(string, optional, tag = "4")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub shape_id: Option<String>,
+        #[prost(string, optional, tag = "5")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub trip_headsign: Option<String>,
+        #[prost(string, optional, tag = "6")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub trip_short_name: Option<String>,
+    }
+}
+
+/// Realtime positioning information for a given vehicle
+#[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VehiclePosition {
+    #[prost(message, optional, tag = "1")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trip: Option<TripDescriptor>,
+    #[prost(message, optional, tag = "8")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vehicle: Option<VehicleDescriptor>,
+    #[prost(message, optional, tag = "2")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<Position>,
+    #[prost(uint32, optional, tag = "3")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_stop_sequence: Option<u32>,
+    #[prost(string, optional, tag = "7")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_id: Option<String>,
+    #[prost(
+        enumeration = "vehicle_position::VehicleStopStatus",
+        optional,
+        tag = "4",
+        default = "InTransitTo"
+    )]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::realtime::json::enumeration::<vehicle_position::VehicleStopStatus, _>"
+    )]
+    pub current_status: Option<i32>,
+    #[prost(uint64, optional, tag = "5")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::realtime::json::int64"
+    )]
+    pub timestamp: Option<u64>,
+    #[prost(enumeration = "vehicle_position::CongestionLevel", optional, tag = "6")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::realtime::json::enumeration::<vehicle_position::CongestionLevel, _>"
+    )]
+    pub congestion_level: Option<i32>,
+    #[prost(enumeration = "vehicle_position::OccupancyStatus", optional, tag = "9")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::realtime::json::enumeration::<vehicle_position::OccupancyStatus, _>"
+    )]
+    pub occupancy_status: Option<i32>,
+    #[prost(uint32, optional, tag = "10")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub occupancy_percentage: Option<u32>,
+    #[prost(message, repeated, tag = "11")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub multi_carriage_details: Vec<vehicle_position::CarriageDetails>,
+}
+
+pub mod vehicle_position {
+    /// Carriage specific details, used for vehicles composed of several
+    #[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CarriageDetails {
+        #[prost(string, optional, tag = "1")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub id: Option<String>,
+        #[prost(string, optional, tag = "2")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub label: Option<String>,
+        #[prost(
+            enumeration = "OccupancyStatus",
+            optional,
+            tag = "3",
+            default = "NoDataAvailable"
+        )]
+        #[serde(
+            skip_serializing_if = "Option::is_none",
+            serialize_with = "crate::realtime::json::enumeration::<OccupancyStatus, _>"
+        )]
+        pub occupancy_status: Option<i32>,
+        #[prost(int32, optional, tag = "4", default = "-1")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub occupancy_percentage: Option<i32>,
+        #[prost(uint32, optional, tag = "5")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub carriage_sequence: Option<u32>,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum VehicleStopStatus {
+        IncomingAt = 0,
+        StoppedAt = 1,
+        InTransitTo = 2,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum CongestionLevel {
+        UnknownCongestionLevel = 0,
+        RunningSmoothly = 1,
+        StopAndGo = 2,
+        Congestion = 3,
+        SevereCongestion = 4,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum OccupancyStatus {
+        Empty = 0,
+        ManySeatsAvailable = 1,
+        FewSeatsAvailable = 2,
+        StandingRoomOnly = 3,
+        CrushedStandingRoomOnly = 4,
+        Full = 5,
+        NotAcceptingPassengers = 6,
+        NoDataAvailable = 7,
+        NotBoardable = 8,
+    }
+}
+
+/// An alert, indicating some sort of incident in the public transit network
+#[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Alert {
+    #[prost(message, repeated, tag = "1")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub active_period: Vec<TimeRange>,
+    #[prost(message, repeated, tag = "5")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub informed_entity: Vec<EntitySelector>,
+    #[prost(
+        enumeration = "alert::Cause",
+        optional,
+        tag = "6",
+        default = "UnknownCause"
+    )]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::realtime::json::enumeration::<alert::Cause, _>"
+    )]
+    pub cause: Option<i32>,
+    #[prost(
+        enumeration = "alert::Effect",
+        optional,
+        tag = "7",
+        default = "UnknownEffect"
+    )]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::realtime::json::enumeration::<alert::Effect, _>"
+    )]
+    pub effect: Option<i32>,
+    #[prost(message, optional, tag = "8")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<TranslatedString>,
+    #[prost(message, optional, tag = "10")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub header_text: Option<TranslatedString>,
+    #[prost(message, optional, tag = "11")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description_text: Option<TranslatedString>,
+    #[prost(message, optional, tag = "12")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tts_header_text: Option<TranslatedString>,
+    #[prost(message, optional, tag = "13")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tts_description_text: Option<TranslatedString>,
+    #[prost(
+        enumeration = "alert::SeverityLevel",
+        optional,
+        tag = "14",
+        default = "UnknownSeverity"
+    )]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::realtime::json::enumeration::<alert::SeverityLevel, _>"
+    )]
+    pub severity_level: Option<i32>,
+    #[prost(message, optional, tag = "15")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<TranslatedImage>,
+    #[prost(message, optional, tag = "16")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_alternative_text: Option<TranslatedString>,
+    #[prost(message, optional, tag = "17")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cause_detail: Option<TranslatedString>,
+    #[prost(message, optional, tag = "18")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effect_detail: Option<TranslatedString>,
+}
+
+pub mod alert {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Cause {
+        UnknownCause = 1,
+        OtherCause = 2,
+        TechnicalProblem = 3,
+        Strike = 4,
+        Demonstration = 5,
+        Accident = 6,
+        Holiday = 7,
+        Weather = 8,
+        Maintenance = 9,
+        Construction = 10,
+        PoliceActivity = 11,
+        MedicalEmergency = 12,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Effect {
+        NoService = 1,
+        ReducedService = 2,
+        SignificantDelays = 3,
+        Detour = 4,
+        AdditionalService = 5,
+        ModifiedService = 6,
+        OtherEffect = 7,
+        UnknownEffect = 8,
+        StopMoved = 9,
+        NoEffect = 10,
+        AccessibilityIssue = 11,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum SeverityLevel {
+        UnknownSeverity = 1,
+        Info = 2,
+        Warning = 3,
+        Severe = 4,
+    }
+}
+
+/// A time interval, in POSIX time
+#[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeRange {
+    #[prost(uint64, optional, tag = "1")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::realtime::json::int64"
+    )]
+    pub start: Option<u64>,
+    #[prost(uint64, optional, tag = "2")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::realtime::json::int64"
+    )]
+    pub end: Option<u64>,
+}
+
+/// A position in WGS-84
+#[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Position {
+    #[prost(float, required, tag = "1")]
+    pub latitude: f32,
+    #[prost(float, required, tag = "2")]
+    pub longitude: f32,
+    #[prost(float, optional, tag = "3")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bearing: Option<f32>,
+    #[prost(double, optional, tag = "4")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub odometer: Option<f64>,
+    #[prost(float, optional, tag = "5")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f32>,
+}
+
+/// Identifies an instance of a GTFS trip, or all instances of a trip along
+/// a route
+#[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TripDescriptor {
+    #[prost(string, optional, tag = "1")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trip_id: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route_id: Option<String>,
+    #[prost(uint32, optional, tag = "6")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction_id: Option<u32>,
+    #[prost(string, optional, tag = "2")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<String>,
+    #[prost(
+        enumeration = "trip_descriptor::ScheduleRelationship",
+        optional,
+        tag = "4"
+    )]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::realtime::json::enumeration::<trip_descriptor::ScheduleRelationship, _>"
+    )]
+    pub schedule_relationship: Option<i32>,
+    #[prost(message, optional, tag = "1001")]
+    #[serde(
+        rename = "[transit_realtime.nyct_trip_descriptor]",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub nyct_trip_descriptor: Option<NyctTripDescriptor>,
+}
+
+pub mod trip_descriptor {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum ScheduleRelationship {
+        Scheduled = 0,
+        Added = 1,
+        Unscheduled = 2,
+        Canceled = 3,
+        Replacement = 5,
+        Duplicated = 6,
+        Deleted = 7,
+    }
+}
+
+/// Identification information for the vehicle performing the trip
+#[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VehicleDescriptor {
+    #[prost(string, optional, tag = "1")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license_plate: Option<String>,
+    #[prost(
+        enumeration = "vehicle_descriptor::WheelchairAccessible",
+        optional,
+        tag = "4",
+        default = "NoValue"
+    )]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::realtime::json::enumeration::<vehicle_descriptor::WheelchairAccessible, _>"
+    )]
+    pub wheelchair_accessible: Option<i32>,
+}
+
+pub mod vehicle_descriptor {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum WheelchairAccessible {
+        NoValue = 0,
+        Unknown = 1,
+        WheelchairAccessible = 2,
+        WheelchairInaccessible = 3,
+    }
+}
+
+/// A selector for an entity in a GTFS feed
+#[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntitySelector {
+    #[prost(string, optional, tag = "1")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agency_id: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route_id: Option<String>,
+    #[prost(int32, optional, tag = "3")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route_type: Option<i32>,
+    #[prost(message, optional, tag = "4")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trip: Option<TripDescriptor>,
+    #[prost(string, optional, tag = "5")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_id: Option<String>,
+    #[prost(uint32, optional, tag = "6")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction_id: Option<u32>,
+}
+
+/// An internationalized message containing per-language versions of a
+/// snippet of text or a URL
+#[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslatedString {
+    #[prost(message, repeated, tag = "1")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub translation: Vec<translated_string::Translation>,
+}
+
+pub mod translated_string {
+    #[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Translation {
+        #[prost(string, required, tag = "1")]
+        pub text: String,
+        #[prost(string, optional, tag = "2")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub language: Option<String>,
+    }
+}
+
+/// An internationalized image containing per-language versions of a URL
+/// linking to an image
+#[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslatedImage {
+    #[prost(message, repeated, tag = "1")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub localized_image: Vec<translated_image::LocalizedImage>,
+}
+
+pub mod translated_image {
+    #[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct LocalizedImage {
+        #[prost(string, required, tag = "1")]
+        pub url: String,
+        #[prost(string, required, tag = "2")]
+        pub media_type: String,
+        #[prost(string, optional, tag = "3")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub language: Option<String>,
+    }
+}
+
+/// NYC Subway extension of `FeedHeader`
+#[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NyctFeedHeader {
+    #[prost(string, required, tag = "1")]
+    pub nyct_subway_version: String,
+    #[prost(message, repeated, tag = "2")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub trip_replacement_period: Vec<TripReplacementPeriod>,
+}
+
+/// NYC Subway period of a route in which trips are replaced by the realtime
+/// feed
+#[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TripReplacementPeriod {
+    #[prost(string, optional, tag = "1")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route_id: Option<String>,
+    #[prost(message, optional, tag = "2")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replacement_period: Option<TimeRange>,
+}
+
+/// NYC Subway extension of `TripDescriptor`
+#[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NyctTripDescriptor {
+    #[prost(string, optional, tag = "1")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub train_id: Option<String>,
+    #[prost(bool, optional, tag = "2")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_assigned: Option<bool>,
+    #[prost(enumeration = "nyct_trip_descriptor::Direction", optional, tag = "3")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::realtime::json::enumeration::<nyct_trip_descriptor::Direction, _>"
+    )]
+    pub direction: Option<i32>,
+}
+
+pub mod nyct_trip_descriptor {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Direction {
+        North = 1,
+        East = 2,
+        South = 3,
+        West = 4,
+    }
+}
+
+/// NYC Subway extension of `StopTimeUpdate`
+#[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NyctStopTimeUpdate {
+    #[prost(string, optional, tag = "1")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheduled_track: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual_track: Option<String>,
+}
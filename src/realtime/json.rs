@@ -0,0 +1,52 @@
+//! Helpers for serializing messages to the protobuf JSON mapping, where
+//! enums are written by name and 64 bit integers as strings
+use serde::Serializer;
+use std::convert::TryFrom;
+use std::fmt::{Debug, Display};
+
+pub fn int64<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Display,
+    S: Serializer,
+{
+    match value {
+        Some(v) => serializer.collect_str(v),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Writes enum values under their .proto name, e.g. `InTransitTo` as
+/// `IN_TRANSIT_TO`, and values unknown to this version as numbers
+pub fn enumeration<E, S>(value: &Option<i32>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    E: TryFrom<i32> + Debug,
+    S: Serializer,
+{
+    match value.map(|v| (v, E::try_from(v))) {
+        Some((_, Ok(e))) => serializer.serialize_str(&proto_name(&format!("{:?}", e))),
+        Some((v, Err(_))) => serializer.serialize_i32(v),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn proto_name(variant: &str) -> String {
+    let mut name = String::with_capacity(variant.len() + 4);
+    for (i, c) in variant.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            name.push('_');
+        }
+        name.push(c.to_ascii_uppercase());
+    }
+    name
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_proto_name() {
+        assert_eq!("IN_TRANSIT_TO", proto_name("InTransitTo"));
+        assert_eq!("NONE", proto_name("None"));
+    }
+}
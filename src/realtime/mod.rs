@@ -0,0 +1,152 @@
+//! GTFS-Realtime feed messages, decoded from and encoded to their protobuf
+//! encoding
+mod gtfs_realtime;
+mod json;
+mod predict;
+mod validate;
+
+pub use self::gtfs_realtime::*;
+pub use self::predict::{PredictionStatus, Schedule, StopTimePrediction, TripPrediction};
+pub use self::validate::{Notice, NoticeKind, Validator};
+use super::{Error, Route, Stop, StopTime, Trip};
+use chrono::NaiveDate;
+use prost::Message;
+use std::convert::TryFrom;
+
+/// Decodes a `FeedMessage` from protobuf bytes
+pub fn decode(bytes: &[u8]) -> Result<FeedMessage, Error> {
+    FeedMessage::decode(bytes).map_err(|e| Error::Feed(format!("{}", e)))
+}
+
+/// Encodes a `FeedMessage` to protobuf bytes
+pub fn encode(message: &FeedMessage) -> Vec<u8> {
+    message.encode_to_vec()
+}
+
+/// Writes a `FeedMessage` in the JSON form of protobuf messages, as used
+/// for debugging feeds
+pub fn to_json(message: &FeedMessage) -> Result<String, Error> {
+    serde_json::to_string_pretty(message).map_err(|e| Error::Feed(format!("{}", e)))
+}
+
+impl FeedMessage {
+    /// An empty full dataset message for GTFS-Realtime 2.0
+    pub fn new(timestamp: u64) -> Self {
+        FeedMessage {
+            header: FeedHeader {
+                gtfs_realtime_version: "2.0".to_string(),
+                incrementality: Some(feed_header::Incrementality::FullDataset as i32),
+                timestamp: Some(timestamp),
+                ..Default::default()
+            },
+            entity: Vec::new(),
+        }
+    }
+
+    pub fn push_trip_update(&mut self, id: &str, trip_update: TripUpdate) {
+        self.entity.push(FeedEntity {
+            id: id.to_string(),
+            trip_update: Some(trip_update),
+            ..Default::default()
+        });
+    }
+
+    pub fn push_vehicle_position(&mut self, id: &str, vehicle: VehiclePosition) {
+        self.entity.push(FeedEntity {
+            id: id.to_string(),
+            vehicle: Some(vehicle),
+            ..Default::default()
+        });
+    }
+
+    pub fn push_alert(&mut self, id: &str, alert: Alert) {
+        self.entity.push(FeedEntity {
+            id: id.to_string(),
+            alert: Some(alert),
+            ..Default::default()
+        });
+    }
+
+    pub fn trip_updates(&self) -> impl Iterator<Item = &TripUpdate> {
+        self.entity.iter().filter_map(|e| e.trip_update.as_ref())
+    }
+
+    pub fn vehicle_positions(&self) -> impl Iterator<Item = &VehiclePosition> {
+        self.entity.iter().filter_map(|e| e.vehicle.as_ref())
+    }
+
+    pub fn alerts(&self) -> impl Iterator<Item = &Alert> {
+        self.entity.iter().filter_map(|e| e.alert.as_ref())
+    }
+}
+
+impl TripDescriptor {
+    /// Describes the run of a static `trip` on the service day `start_date`
+    pub fn for_trip(trip: &Trip, start_date: NaiveDate) -> Self {
+        TripDescriptor {
+            trip_id: Some(trip.trip_id.clone()),
+            route_id: Some(trip.route_id.clone()),
+            direction_id: trip.direction_id.as_deref().and_then(|d| d.parse().ok()),
+            start_date: Some(start_date.format("%Y%m%d").to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+impl trip_update::StopTimeUpdate {
+    /// An update without events for the stop of a static `stop_time`
+    pub fn for_stop_time(stop_time: &StopTime) -> Self {
+        trip_update::StopTimeUpdate {
+            stop_sequence: u32::try_from(stop_time.stop_sequence).ok(),
+            stop_id: Some(stop_time.stop_id.clone()),
+            ..Default::default()
+        }
+    }
+}
+
+impl EntitySelector {
+    pub fn for_route(route: &Route) -> Self {
+        EntitySelector {
+            agency_id: route.agency_id.clone(),
+            route_id: Some(route.route_id.clone()),
+            ..Default::default()
+        }
+    }
+
+    pub fn for_stop(stop: &Stop) -> Self {
+        EntitySelector {
+            stop_id: Some(stop.stop_id.clone()),
+            ..Default::default()
+        }
+    }
+
+    pub fn for_trip(trip: &Trip, start_date: NaiveDate) -> Self {
+        EntitySelector {
+            trip: Some(TripDescriptor::for_trip(trip, start_date)),
+            ..Default::default()
+        }
+    }
+}
+
+impl TranslatedString {
+    /// A single translation without a language
+    pub fn new(text: &str) -> Self {
+        TranslatedString {
+            translation: vec![translated_string::Translation {
+                text: text.to_string(),
+                language: None,
+            }],
+        }
+    }
+
+    /// Text for `language`, falling back to the translation without a
+    /// language and then to the first translation
+    pub fn text(&self, language: Option<&str>) -> Option<&str> {
+        self.translation
+            .iter()
+            .find(|t| language.is_some() && t.language.as_deref() == language)
+            .or_else(|| self.translation.iter().find(|t| t.language.is_none()))
+            .or_else(|| self.translation.first())
+            .map(|t| t.text.as_str())
+    }
+}
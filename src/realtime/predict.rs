@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, TimeZone};
+
+use super::gtfs_realtime::trip_descriptor::ScheduleRelationship as TripRelationship;
+use super::gtfs_realtime::trip_update::stop_time_update::ScheduleRelationship;
+use super::gtfs_realtime::trip_update::{StopTimeEvent, StopTimeUpdate};
+use super::gtfs_realtime::TripUpdate;
+use crate::feed::Feed;
+use crate::{Error, StopTime, TimeOffset};
+
+/// Where the times of a `StopTimePrediction` come from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PredictionStatus {
+    /// No realtime information reached this stop, times are as scheduled
+    Scheduled,
+    /// The trip update has a prediction for this stop
+    Predicted,
+    /// The delay of an earlier stop was carried over to this stop
+    Propagated,
+    /// The vehicle will not stop here
+    Skipped,
+    /// The trip update has no data for this stop
+    NoData,
+    /// The whole trip was canceled
+    Canceled,
+}
+
+/// Predicted arrival and departure at one stop of a trip. Times are POSIX
+/// timestamps, scheduled times are absent for trips added in realtime.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StopTimePrediction {
+    pub stop_sequence: u64,
+    pub stop_id: String,
+    pub scheduled_arrival: Option<i64>,
+    pub scheduled_departure: Option<i64>,
+    pub arrival: Option<i64>,
+    pub departure: Option<i64>,
+    pub status: PredictionStatus,
+}
+
+impl StopTimePrediction {
+    pub fn arrival_delay(&self) -> Option<i64> {
+        Some(self.arrival? - self.scheduled_arrival?)
+    }
+
+    pub fn departure_delay(&self) -> Option<i64> {
+        Some(self.departure? - self.scheduled_departure?)
+    }
+}
+
+/// The predicted stop times of a trip on one service day
+#[derive(Clone, Debug, PartialEq)]
+pub struct TripPrediction {
+    pub trip_id: String,
+    pub service_date: NaiveDate,
+    pub stop_times: Vec<StopTimePrediction>,
+}
+
+/// Schedule
+/// Static stop times of a feed grouped by trip and ordered by
+/// stop_sequence, ready to have trip updates applied to them
+#[derive(Debug)]
+pub struct Schedule<'a> {
+    trips: HashMap<&'a str, Vec<&'a StopTime>>,
+}
+
+impl<'a> Schedule<'a> {
+    pub fn new(stop_times: &'a [StopTime]) -> Self {
+        let mut trips: HashMap<&str, Vec<&StopTime>> = HashMap::new();
+        for stop_time in stop_times {
+            trips
+                .entry(stop_time.trip_id.as_str())
+                .or_default()
+                .push(stop_time);
+        }
+        for stop_times in trips.values_mut() {
+            stop_times.sort_by_key(|st| st.stop_sequence);
+        }
+        Schedule { trips }
+    }
+
+    pub fn from_feed(feed: &'a Feed) -> Self {
+        Schedule::new(&feed.stop_times)
+    }
+
+    /// Scheduled stop times of `trip_id` ordered by stop_sequence
+    pub fn stop_times(&self, trip_id: &str) -> Option<&[&'a StopTime]> {
+        self.trips.get(trip_id).map(|st| st.as_slice())
+    }
+
+    /// Applies `update` to the schedule of its trip. Times are resolved
+    /// against the service day given by the trip's start_date in `tz`, the
+    /// timezone of the agency.
+    ///
+    /// Stop time updates are matched by stop_sequence, or by stop_id when
+    /// no sequence is given. Updates matching no stop of the trip are
+    /// ignored. The delay of each updated stop carries over to the
+    /// following stops until the next update, SKIPPED stops keep the delay
+    /// going while NO_DATA stops end it.
+    pub fn predict<Tz: TimeZone>(
+        &self,
+        update: &TripUpdate,
+        tz: &Tz,
+    ) -> Result<TripPrediction, Error> {
+        let trip = &update.trip;
+        let trip_id = trip.trip_id.as_deref().unwrap_or_default();
+        let properties = update.trip_properties.as_ref();
+        let start_date = properties
+            .and_then(|p| p.start_date.as_deref())
+            .or(trip.start_date.as_deref())
+            .ok_or_else(|| Error::Feed(format!("trip update for {} has no start_date", trip_id)))?;
+        let service_date = NaiveDate::parse_from_str(start_date, "%Y%m%d")
+            .map_err(|e| Error::Feed(format!("invalid start_date {} - {}", start_date, e)))?;
+        let service_day = service_day_start(service_date, tz)?;
+
+        let relationship = trip.schedule_relationship();
+        if relationship == TripRelationship::Added {
+            return Ok(TripPrediction {
+                trip_id: trip_id.to_string(),
+                service_date,
+                stop_times: added_stop_times(update),
+            });
+        }
+
+        let scheduled = self
+            .stop_times(trip_id)
+            .ok_or_else(|| Error::Feed(format!("trip {} is not in the schedule", trip_id)))?;
+        let (trip_id, shift) = match relationship {
+            TripRelationship::Duplicated => {
+                let start_time = properties
+                    .and_then(|p| p.start_time.as_deref())
+                    .or(trip.start_time.as_deref());
+                let shift = match (start_time, scheduled.first()) {
+                    (Some(start_time), Some(first)) => {
+                        let start: TimeOffset = start_time.parse().map_err(|e| {
+                            Error::Feed(format!("invalid start_time {} - {}", start_time, e))
+                        })?;
+                        seconds(&start) - seconds(&first.departure_time)
+                    }
+                    _ => 0,
+                };
+                let trip_id = properties
+                    .and_then(|p| p.trip_id.as_deref())
+                    .unwrap_or(trip_id);
+                (trip_id, shift)
+            }
+            _ => (trip_id, 0),
+        };
+
+        let mut stop_times: Vec<StopTimePrediction> = scheduled
+            .iter()
+            .map(|st| {
+                let arrival = service_day + shift + seconds(&st.arrival_time);
+                let departure = service_day + shift + seconds(&st.departure_time);
+                StopTimePrediction {
+                    stop_sequence: st.stop_sequence,
+                    stop_id: st.stop_id.clone(),
+                    scheduled_arrival: Some(arrival),
+                    scheduled_departure: Some(departure),
+                    arrival: Some(arrival),
+                    departure: Some(departure),
+                    status: PredictionStatus::Scheduled,
+                }
+            })
+            .collect();
+
+        match relationship {
+            TripRelationship::Canceled | TripRelationship::Deleted => {
+                for prediction in stop_times.iter_mut() {
+                    prediction.arrival = None;
+                    prediction.departure = None;
+                    prediction.status = PredictionStatus::Canceled;
+                }
+            }
+            _ => apply_updates(&mut stop_times, update),
+        }
+
+        Ok(TripPrediction {
+            trip_id: trip_id.to_string(),
+            service_date,
+            stop_times,
+        })
+    }
+}
+
+/// GTFS times are measured from noon minus 12h of the service day, which
+/// is midnight except on days when daylight saving time changes
+fn service_day_start<Tz: TimeZone>(date: NaiveDate, tz: &Tz) -> Result<i64, Error> {
+    let noon = date
+        .and_hms_opt(12, 0, 0)
+        .and_then(|noon| tz.from_local_datetime(&noon).earliest())
+        .ok_or_else(|| Error::Feed(format!("no noon on service day {}", date)))?;
+    Ok(noon.timestamp() - 12 * 60 * 60)
+}
+
+fn seconds(time: &TimeOffset) -> i64 {
+    time.duration().num_seconds()
+}
+
+fn event_delay(event: Option<&StopTimeEvent>, scheduled: Option<i64>) -> Option<i64> {
+    let event = event?;
+    match (event.time, scheduled) {
+        (Some(time), Some(scheduled)) => Some(time - scheduled),
+        _ => event.delay.map(i64::from),
+    }
+}
+
+fn find_update(
+    stop_times: &[StopTimePrediction],
+    update: &StopTimeUpdate,
+    from: usize,
+) -> Option<usize> {
+    match (update.stop_sequence, update.stop_id.as_deref()) {
+        (Some(sequence), _) => stop_times
+            .iter()
+            .position(|st| st.stop_sequence == u64::from(sequence)),
+        (None, Some(stop_id)) => stop_times[from..]
+            .iter()
+            .position(|st| st.stop_id == stop_id)
+            .map(|i| i + from),
+        (None, None) => None,
+    }
+}
+
+fn apply_updates(stop_times: &mut [StopTimePrediction], update: &TripUpdate) {
+    let mut matched = vec![None; stop_times.len()];
+    let mut from = 0;
+    for stop_time_update in &update.stop_time_update {
+        if let Some(i) = find_update(stop_times, stop_time_update, from) {
+            matched[i] = Some(stop_time_update);
+            from = i + 1;
+        }
+    }
+
+    let mut delay = update.delay.map(i64::from);
+    let mut no_data = false;
+    for (prediction, stop_time_update) in stop_times.iter_mut().zip(matched) {
+        let relationship = stop_time_update.map(|u| u.schedule_relationship());
+        match (stop_time_update, relationship) {
+            (Some(_), Some(ScheduleRelationship::Skipped)) => {
+                prediction.arrival = None;
+                prediction.departure = None;
+                prediction.status = PredictionStatus::Skipped;
+            }
+            (Some(_), Some(ScheduleRelationship::NoData)) => {
+                delay = None;
+                no_data = true;
+            }
+            (Some(u), _) => {
+                let arrival_delay = event_delay(u.arrival.as_ref(), prediction.scheduled_arrival);
+                let departure_delay =
+                    event_delay(u.departure.as_ref(), prediction.scheduled_departure);
+                let arrival_delay = arrival_delay.or(departure_delay).or(delay);
+                let departure_delay = departure_delay.or(arrival_delay);
+                shift_prediction(prediction, arrival_delay, departure_delay);
+                prediction.status = PredictionStatus::Predicted;
+                delay = departure_delay;
+                no_data = false;
+            }
+            (None, _) => match delay {
+                Some(d) if !no_data => {
+                    shift_prediction(prediction, Some(d), Some(d));
+                    prediction.status = PredictionStatus::Propagated;
+                }
+                _ => (),
+            },
+        }
+        if no_data && prediction.status != PredictionStatus::Skipped {
+            prediction.arrival = None;
+            prediction.departure = None;
+            prediction.status = PredictionStatus::NoData;
+        }
+    }
+}
+
+fn shift_prediction(
+    prediction: &mut StopTimePrediction,
+    arrival_delay: Option<i64>,
+    departure_delay: Option<i64>,
+) {
+    prediction.arrival = prediction
+        .scheduled_arrival
+        .map(|t| t + arrival_delay.unwrap_or(0));
+    prediction.departure = prediction
+        .scheduled_departure
+        .map(|t| t + departure_delay.unwrap_or(0))
+        .map(|t| match prediction.arrival {
+            Some(arrival) => t.max(arrival),
+            None => t,
+        });
+}
+
+/// Trips added in realtime have no schedule, their stop times come only
+/// from absolute times in the update
+fn added_stop_times(update: &TripUpdate) -> Vec<StopTimePrediction> {
+    update
+        .stop_time_update
+        .iter()
+        .enumerate()
+        .map(|(i, u)| {
+            let skipped = u.schedule_relationship() == ScheduleRelationship::Skipped;
+            let arrival = u.arrival.as_ref().and_then(|e| e.time);
+            let departure = u.departure.as_ref().and_then(|e| e.time);
+            StopTimePrediction {
+                stop_sequence: u.stop_sequence.map_or(i as u64 + 1, u64::from),
+                stop_id: u.stop_id.clone().unwrap_or_default(),
+                scheduled_arrival: None,
+                scheduled_departure: None,
+                arrival: if skipped { None } else { arrival.or(departure) },
+                departure: if skipped { None } else { departure.or(arrival) },
+                status: if skipped {
+                    PredictionStatus::Skipped
+                } else {
+                    PredictionStatus::Predicted
+                },
+            }
+        })
+        .collect()
+}
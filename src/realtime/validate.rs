@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::TimeZone;
+
+use super::gtfs_realtime::trip_descriptor::ScheduleRelationship as TripRelationship;
+use super::gtfs_realtime::{FeedMessage, TripUpdate, VehiclePosition};
+use super::predict::Schedule;
+use crate::distance::{haversine, project_onto_segment};
+use crate::feed::Feed;
+use crate::{ShapePoint, Stop, Trip};
+
+/// What is wrong with an entity of a realtime feed
+#[derive(Clone, Debug, PartialEq)]
+pub enum NoticeKind {
+    /// The trip_id is not in trips.txt
+    UnknownTrip { trip_id: String },
+    /// No stop time of the trip has this stop_sequence
+    UnknownStopSequence { trip_id: String, stop_sequence: u32 },
+    /// The stop_id is not visited by the trip, or not at the given
+    /// stop_sequence
+    StopNotOnTrip { trip_id: String, stop_id: String },
+    /// The trip update could not be applied to the schedule
+    InvalidTripUpdate { trip_id: String, message: String },
+    /// A predicted time is earlier than the one of the stop before
+    NonMonotonicTimes { trip_id: String, stop_sequence: u64 },
+    /// The vehicle is further than allowed from the shape of its trip
+    VehicleOffShape { trip_id: String, distance: f64 },
+    /// The timestamp is older than allowed
+    StaleTimestamp { timestamp: u64, age: u64 },
+}
+
+/// A problem found by `Validator`, for the entity with `entity_id` or for
+/// the header when it is `None`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Notice {
+    pub entity_id: Option<String>,
+    pub kind: NoticeKind,
+}
+
+impl fmt::Display for Notice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.entity_id {
+            Some(id) => write!(f, "entity {} - ", id)?,
+            None => write!(f, "header - ")?,
+        }
+        match &self.kind {
+            NoticeKind::UnknownTrip { trip_id } => write!(f, "unknown trip {}", trip_id),
+            NoticeKind::UnknownStopSequence {
+                trip_id,
+                stop_sequence,
+            } => write!(f, "trip {} has no stop_sequence {}", trip_id, stop_sequence),
+            NoticeKind::StopNotOnTrip { trip_id, stop_id } => {
+                write!(f, "stop {} is not on trip {}", stop_id, trip_id)
+            }
+            NoticeKind::InvalidTripUpdate { trip_id, message } => {
+                write!(f, "invalid update for trip {} - {}", trip_id, message)
+            }
+            NoticeKind::NonMonotonicTimes {
+                trip_id,
+                stop_sequence,
+            } => write!(
+                f,
+                "trip {} goes back in time at stop_sequence {}",
+                trip_id, stop_sequence
+            ),
+            NoticeKind::VehicleOffShape { trip_id, distance } => {
+                write!(f, "vehicle is {:.0}m off trip {}", distance, trip_id)
+            }
+            NoticeKind::StaleTimestamp { timestamp, age } => {
+                write!(f, "timestamp {} is {}s old", timestamp, age)
+            }
+        }
+    }
+}
+
+/// Validator
+/// Checks realtime feed messages against the static feed they refer to
+pub struct Validator<'a, Tz: TimeZone> {
+    schedule: Schedule<'a>,
+    trips: HashMap<&'a str, &'a Trip>,
+    stops: HashMap<&'a str, &'a Stop>,
+    shapes: HashMap<&'a str, Vec<&'a ShapePoint>>,
+    tz: Tz,
+    /// Furthest a vehicle may be from its trip's shape, in meters
+    pub max_shape_distance: f64,
+    /// Oldest a timestamp may be, in seconds
+    pub max_age: u64,
+}
+
+impl<'a, Tz: TimeZone> Validator<'a, Tz> {
+    /// Validates against `feed`, whose times are in `tz`
+    pub fn new(feed: &'a Feed, tz: Tz) -> Self {
+        Validator {
+            schedule: Schedule::from_feed(feed),
+            trips: feed.trips.iter().map(|t| (t.trip_id.as_str(), t)).collect(),
+            stops: feed.stops.iter().map(|s| (s.stop_id.as_str(), s)).collect(),
+            shapes: feed.shapes_by_id(),
+            tz,
+            max_shape_distance: 200.0,
+            max_age: 90,
+        }
+    }
+
+    /// Returns the notices for `message` as of `now`, a POSIX timestamp
+    pub fn validate(&self, message: &FeedMessage, now: u64) -> Vec<Notice> {
+        let mut notices = Vec::new();
+        self.check_timestamp(None, message.header.timestamp, now, &mut notices);
+        for entity in &message.entity {
+            let mut push = |kind| {
+                notices.push(Notice {
+                    entity_id: Some(entity.id.clone()),
+                    kind,
+                })
+            };
+            if let Some(update) = &entity.trip_update {
+                self.validate_trip_update(update, &mut push);
+            }
+            if let Some(vehicle) = &entity.vehicle {
+                self.validate_vehicle(vehicle, &mut push);
+            }
+            let timestamps = [
+                entity.trip_update.as_ref().and_then(|u| u.timestamp),
+                entity.vehicle.as_ref().and_then(|v| v.timestamp),
+            ];
+            for timestamp in timestamps.iter() {
+                self.check_timestamp(Some(&entity.id), *timestamp, now, &mut notices);
+            }
+        }
+        notices
+    }
+
+    fn check_timestamp(
+        &self,
+        entity_id: Option<&str>,
+        timestamp: Option<u64>,
+        now: u64,
+        notices: &mut Vec<Notice>,
+    ) {
+        if let Some(timestamp) = timestamp {
+            let age = now.saturating_sub(timestamp);
+            if age > self.max_age {
+                notices.push(Notice {
+                    entity_id: entity_id.map(str::to_string),
+                    kind: NoticeKind::StaleTimestamp { timestamp, age },
+                });
+            }
+        }
+    }
+
+    fn validate_trip_update<F: FnMut(NoticeKind)>(&self, update: &TripUpdate, push: &mut F) {
+        let trip_id = match update.trip.trip_id.as_deref() {
+            Some(trip_id) => trip_id,
+            None => return,
+        };
+        let added = update.trip.schedule_relationship() == TripRelationship::Added;
+        if !added && !self.trips.contains_key(trip_id) {
+            push(NoticeKind::UnknownTrip {
+                trip_id: trip_id.to_string(),
+            });
+            return;
+        }
+
+        if !added {
+            let stop_times = self.schedule.stop_times(trip_id).unwrap_or_default();
+            for stop_time_update in &update.stop_time_update {
+                let stop_id = stop_time_update.stop_id.as_deref();
+                let on_trip = match stop_time_update.stop_sequence {
+                    Some(sequence) => {
+                        match stop_times
+                            .iter()
+                            .find(|st| st.stop_sequence == u64::from(sequence))
+                        {
+                            Some(st) => stop_id.is_none_or(|id| id == st.stop_id),
+                            None => {
+                                push(NoticeKind::UnknownStopSequence {
+                                    trip_id: trip_id.to_string(),
+                                    stop_sequence: sequence,
+                                });
+                                continue;
+                            }
+                        }
+                    }
+                    None => stop_id.is_none_or(|id| stop_times.iter().any(|st| st.stop_id == id)),
+                };
+                if !on_trip {
+                    push(NoticeKind::StopNotOnTrip {
+                        trip_id: trip_id.to_string(),
+                        stop_id: stop_id.unwrap_or_default().to_string(),
+                    });
+                }
+            }
+        }
+
+        let prediction = match self.schedule.predict(update, &self.tz) {
+            Ok(prediction) => prediction,
+            Err(e) => {
+                return push(NoticeKind::InvalidTripUpdate {
+                    trip_id: trip_id.to_string(),
+                    message: format!("{}", e),
+                })
+            }
+        };
+        let mut last = None;
+        for stop_time in &prediction.stop_times {
+            for time in [stop_time.arrival, stop_time.departure].iter().flatten() {
+                if last.is_some_and(|last| *time < last) {
+                    push(NoticeKind::NonMonotonicTimes {
+                        trip_id: trip_id.to_string(),
+                        stop_sequence: stop_time.stop_sequence,
+                    });
+                    return;
+                }
+                last = Some(*time);
+            }
+        }
+    }
+
+    fn validate_vehicle<F: FnMut(NoticeKind)>(&self, vehicle: &VehiclePosition, push: &mut F) {
+        let trip = match vehicle.trip.as_ref() {
+            Some(trip) if trip.schedule_relationship() != TripRelationship::Added => trip,
+            _ => return,
+        };
+        let trip_id = match trip.trip_id.as_deref() {
+            Some(trip_id) => trip_id,
+            None => return,
+        };
+        let static_trip = match self.trips.get(trip_id) {
+            Some(static_trip) => static_trip,
+            None => {
+                return push(NoticeKind::UnknownTrip {
+                    trip_id: trip_id.to_string(),
+                })
+            }
+        };
+        let position = match vehicle.position.as_ref() {
+            Some(position) => position,
+            None => return,
+        };
+        let point = (f64::from(position.latitude), f64::from(position.longitude));
+        let line = self.trip_line(static_trip);
+        if let Some(distance) = distance_to_line(point, &line) {
+            if distance > self.max_shape_distance {
+                push(NoticeKind::VehicleOffShape {
+                    trip_id: trip_id.to_string(),
+                    distance,
+                });
+            }
+        }
+    }
+
+    /// The shape of `trip`, or the line through its stops if it has none
+    fn trip_line(&self, trip: &Trip) -> Vec<(f64, f64)> {
+        let shape = trip.shape_id.as_deref().and_then(|id| self.shapes.get(id));
+        match shape {
+            Some(points) => points
+                .iter()
+                .map(|p| (p.shape_pt_lat, p.shape_pt_lon))
+                .collect(),
+            None => self
+                .schedule
+                .stop_times(&trip.trip_id)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|st| self.stops.get(st.stop_id.as_str()))
+                .map(|s| (s.stop_lat, s.stop_lon))
+                .collect(),
+        }
+    }
+}
+
+/// Distance in meters from `point` to the closest point of the line
+/// through `points`. Distances are measured on a plane tangent at `point`,
+/// which is accurate for the short distances between points of a shape.
+fn distance_to_line(point: (f64, f64), points: &[(f64, f64)]) -> Option<f64> {
+    let mut min = haversine(point, *points.first()?);
+    for pair in points.windows(2) {
+        min = min.min(project_onto_segment(point, pair[0], pair[1]).1);
+    }
+    Some(min)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_distance_to_line() {
+        let line = [(0.0, 0.0), (0.0, 1.0)];
+        let d = distance_to_line((0.001, 0.5), &line).unwrap();
+        assert!((d - 111.2).abs() < 0.1);
+        let d = distance_to_line((0.0, 1.001), &line).unwrap();
+        assert!((d - 111.2).abs() < 0.1);
+        assert_eq!(None, distance_to_line((0.0, 0.0), &[]));
+    }
+}
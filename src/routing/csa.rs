@@ -0,0 +1,331 @@
+use std::cmp::Reverse;
+
+use chrono::{Duration, NaiveDate};
+
+use super::{
+    can_alight, can_board, day_trips, to_offset, DayTrip, Journey, Leg, Stops, Time, Transfers,
+    DAY, NEVER,
+};
+use crate::feed::Feed;
+use crate::intern::{Idx, StopIdx};
+
+/// A vehicle going from one stop to the next without stopping
+#[derive(Debug)]
+struct Connection {
+    trip: usize,
+    /// Position of the departure stop in the trip
+    position: usize,
+    from: StopIdx,
+    to: StopIdx,
+    departure: Time,
+    arrival: Time,
+}
+
+/// What follows getting off a trip
+#[derive(Clone, Copy, Debug)]
+enum Exit {
+    /// The target is reached, walking there if it isn't the stop itself
+    Target,
+    /// Another trip is taken from the stop or reached by walking
+    Transfer,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Start {
+    /// Take the trip of connection `board` up to the arrival of connection
+    /// `alight`
+    Board {
+        board: usize,
+        alight: usize,
+        exit: Exit,
+    },
+    /// Walk to `to` and board a trip there
+    Walk { to: StopIdx, duration: Time },
+}
+
+/// A departure from a stop and the arrival at the target it leads to
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    departure: Time,
+    arrival: Time,
+    /// Time needed at the stop before `departure` when arriving by trip
+    change: Time,
+    start: Start,
+}
+
+impl Entry {
+    /// Latest arrival by trip to still make this departure
+    fn latest_arrival(&self) -> Time {
+        self.departure.saturating_sub(self.change)
+    }
+
+    fn dominates(&self, other: &Entry) -> bool {
+        self.departure >= other.departure
+            && self.latest_arrival() >= other.latest_arrival()
+            && self.arrival <= other.arrival
+    }
+}
+
+/// Pareto set of the entries of a stop
+#[derive(Clone, Debug, Default)]
+struct Profile {
+    entries: Vec<Entry>,
+}
+
+impl Profile {
+    fn insert(&mut self, entry: Entry) {
+        if self.entries.iter().any(|e| e.dominates(&entry)) {
+            return;
+        }
+        self.entries.retain(|e| !entry.dominates(e));
+        self.entries.push(entry);
+    }
+
+    /// Best entry for someone arriving by trip at `time`
+    fn after_trip(&self, time: Time) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .filter(|e| e.latest_arrival() >= time)
+            .min_by_key(|e| e.arrival)
+    }
+
+    /// Best entry boarding a trip at the stop for someone arriving on foot
+    /// at `time`
+    fn after_walk(&self, time: Time) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .filter(|e| matches!(e.start, Start::Board { .. }) && e.departure >= time)
+            .min_by_key(|e| e.arrival)
+    }
+}
+
+/// How riding a trip from a connection on gets to the target soonest
+#[derive(Clone, Copy, Debug)]
+struct TripExit {
+    arrival: Time,
+    alight: usize,
+    exit: Exit,
+}
+
+/// ConnectionScan
+/// The Connection Scan Algorithm (Dibbelt et al.) over the trips of a feed
+/// running on one date, answering profile queries
+#[derive(Debug)]
+pub struct ConnectionScan<'a> {
+    stops: Stops<'a>,
+    transfers: Transfers,
+    /// Footpaths by the stop they lead to
+    incoming: Vec<Vec<(StopIdx, Time)>>,
+    trips: Vec<DayTrip<'a>>,
+    times: Vec<Vec<(Time, Time)>>,
+    /// Sorted by departure
+    connections: Vec<Connection>,
+}
+
+impl<'a> ConnectionScan<'a> {
+    pub fn new(feed: &'a Feed, date: NaiveDate) -> Self {
+        let stops = Stops::new(feed);
+        let transfers = Transfers::new(&feed.transfers, &stops);
+        let mut incoming = vec![Vec::new(); stops.len()];
+        for (from, footpaths) in transfers.footpaths.iter().enumerate() {
+            for &(to, duration) in footpaths {
+                incoming[to.index()].push((StopIdx::from_index(from), duration));
+            }
+        }
+
+        let trips = day_trips(feed, &stops, date);
+        let times: Vec<_> = trips.iter().map(|t| t.times(date)).collect();
+        let mut connections = Vec::new();
+        for (t, trip) in trips.iter().enumerate() {
+            for position in 0..trip.stops.len() - 1 {
+                connections.push(Connection {
+                    trip: t,
+                    position,
+                    from: trip.stops[position],
+                    to: trip.stops[position + 1],
+                    departure: times[t][position].1,
+                    arrival: times[t][position + 1].0,
+                });
+            }
+        }
+        // Later connections of a trip come first when scanning backwards,
+        // even when they share times
+        connections.sort_by_key(|c| (c.departure, c.arrival, c.position));
+
+        ConnectionScan {
+            stops,
+            transfers,
+            incoming,
+            trips,
+            times,
+            connections,
+        }
+    }
+
+    /// Every journey from `from` to `to` over the day that no other journey
+    /// beats by leaving later and arriving no later, ordered by departure
+    pub fn profile(&self, from: &str, to: &str) -> Vec<Journey<'a>> {
+        let (origin, target) = match (self.stops.get(from), self.stops.get(to)) {
+            (Some(origin), Some(target)) => (origin, target),
+            _ => return Vec::new(),
+        };
+        let profiles = self.scan(target);
+
+        // Entries differing only in the time needed to change are kept in
+        // the profile, but the traveller starting here needs none
+        let mut entries = profiles[origin.index()].entries.clone();
+        // Departures before midnight belong to the day before
+        entries.retain(|e| e.departure >= DAY);
+        entries.sort_by_key(|e| (Reverse(e.departure), e.arrival));
+        let mut journeys = Vec::new();
+        let mut arrival = NEVER;
+        for entry in &entries {
+            if entry.arrival < arrival {
+                arrival = entry.arrival;
+                journeys.push(self.journey(&profiles, origin, target, entry));
+            }
+        }
+        journeys.reverse();
+        journeys
+    }
+
+    fn scan(&self, target: StopIdx) -> Vec<Profile> {
+        let mut to_target = vec![NEVER; self.stops.len()];
+        to_target[target.index()] = 0;
+        for &(from, duration) in &self.incoming[target.index()] {
+            to_target[from.index()] = to_target[from.index()].min(duration);
+        }
+
+        let mut profiles = vec![Profile::default(); self.stops.len()];
+        let mut trip_exits: Vec<Option<TripExit>> = vec![None; self.trips.len()];
+        for (i, c) in self.connections.iter().enumerate().rev() {
+            let stop_times = &self.trips[c.trip].stop_times;
+            let mut best = trip_exits[c.trip];
+            if can_alight(stop_times[c.position + 1]) {
+                let walk = to_target[c.to.index()];
+                let candidates = [
+                    (walk != NEVER).then(|| (c.arrival + walk, Exit::Target)),
+                    profiles[c.to.index()]
+                        .after_trip(c.arrival)
+                        .map(|e| (e.arrival, Exit::Transfer)),
+                ];
+                for (arrival, exit) in candidates.iter().flatten() {
+                    if best.is_none_or(|b| *arrival < b.arrival) {
+                        best = Some(TripExit {
+                            arrival: *arrival,
+                            alight: i,
+                            exit: *exit,
+                        });
+                    }
+                }
+            }
+            let best = match best {
+                Some(best) => best,
+                None => continue,
+            };
+            trip_exits[c.trip] = Some(best);
+            if !can_board(stop_times[c.position]) {
+                continue;
+            }
+
+            let change = self.transfers.change_time(c.from).unwrap_or(NEVER);
+            let start = Start::Board {
+                board: i,
+                alight: best.alight,
+                exit: best.exit,
+            };
+            profiles[c.from.index()].insert(Entry {
+                departure: c.departure,
+                arrival: best.arrival,
+                change,
+                start,
+            });
+            for &(from, duration) in &self.incoming[c.from.index()] {
+                if let Some(departure) = c.departure.checked_sub(duration) {
+                    profiles[from.index()].insert(Entry {
+                        departure,
+                        arrival: best.arrival,
+                        change: 0,
+                        start: Start::Walk {
+                            to: c.from,
+                            duration,
+                        },
+                    });
+                }
+            }
+        }
+        profiles
+    }
+
+    fn journey(
+        &self,
+        profiles: &[Profile],
+        origin: StopIdx,
+        target: StopIdx,
+        entry: &Entry,
+    ) -> Journey<'a> {
+        let mut legs = Vec::new();
+        let mut stop = origin;
+        let mut next = Some(*entry);
+        while let Some(entry) = next.take() {
+            match entry.start {
+                Start::Walk { to, duration } => {
+                    legs.push(self.walk(stop, to, duration));
+                    next = profiles[to.index()]
+                        .after_walk(entry.departure + duration)
+                        .copied();
+                    stop = to;
+                }
+                Start::Board {
+                    board,
+                    alight,
+                    exit,
+                } => {
+                    let board = &self.connections[board];
+                    let alight = &self.connections[alight];
+                    let trip = &self.trips[board.trip];
+                    let times = &self.times[board.trip];
+                    legs.push(Leg::Transit {
+                        trip: trip.trip,
+                        service_date: trip.service_date,
+                        from: trip.stop_times[board.position],
+                        to: trip.stop_times[alight.position + 1],
+                        departure: to_offset(times[board.position].1),
+                        arrival: to_offset(times[alight.position + 1].0),
+                    });
+                    stop = alight.to;
+                    match exit {
+                        Exit::Target if stop != target => {
+                            let duration = self
+                                .transfers
+                                .footpaths(stop)
+                                .iter()
+                                .filter(|(to, _)| *to == target)
+                                .map(|(_, duration)| *duration)
+                                .min()
+                                .unwrap_or_default();
+                            legs.push(self.walk(stop, target, duration));
+                        }
+                        Exit::Target => (),
+                        Exit::Transfer => {
+                            next = profiles[stop.index()].after_trip(alight.arrival).copied();
+                        }
+                    }
+                }
+            }
+        }
+        Journey {
+            departure: to_offset(entry.departure),
+            arrival: to_offset(entry.arrival),
+            legs,
+        }
+    }
+
+    fn walk(&self, from: StopIdx, to: StopIdx, duration: Time) -> Leg<'a> {
+        Leg::Walk {
+            from: self.stops.stop(from),
+            to: self.stops.stop(to),
+            duration: Duration::seconds(i64::from(duration)),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::HashSet;
+
+use chrono::Duration;
+
+use crate::distance::{haversine, latitude_degrees};
+use crate::feed::Feed;
+use crate::{LocationType, Stop, Transfer, TransferType};
+
+/// A walking link from one stop to another
+#[derive(Debug)]
+pub struct Footpath<'a> {
+    pub from: &'a Stop,
+    pub to: &'a Stop,
+    /// Straight line distance in meters
+    pub distance: f64,
+    pub duration: Duration,
+}
+
+impl<'a> Footpath<'a> {
+    /// The footpath as a transfers.txt record
+    pub fn to_transfer(&self) -> Transfer {
+        Transfer {
+            from_stop_id: self.from.stop_id.clone(),
+            to_stop_id: self.to.stop_id.clone(),
+            transfer_type: TransferType::MinimumTime,
+            min_transfer_time: Some(self.duration),
+        }
+    }
+}
+
+/// FootpathGenerator
+/// Links stops close enough to walk between, as transfers.txt rarely lists
+/// them all. Walking times assume a straight line at `walking_speed`.
+#[derive(Debug)]
+pub struct FootpathGenerator {
+    /// Furthest distance to walk, in meters
+    pub radius: f64,
+    /// In meters per second
+    pub walking_speed: f64,
+}
+
+impl Default for FootpathGenerator {
+    fn default() -> Self {
+        FootpathGenerator {
+            radius: 400.0,
+            walking_speed: 1.4,
+        }
+    }
+}
+
+impl FootpathGenerator {
+    pub fn new(radius: f64, walking_speed: f64) -> Self {
+        FootpathGenerator {
+            radius,
+            walking_speed,
+        }
+    }
+
+    /// Footpaths in both directions between stops of `feed` within
+    /// `radius`. Stations are left out, as are pairs of stops already in
+    /// transfers.txt, so its times and `NotPossible` transfers take
+    /// precedence.
+    pub fn generate<'a>(&self, feed: &'a Feed) -> Vec<Footpath<'a>> {
+        let listed: HashSet<(&str, &str)> = feed
+            .transfers
+            .iter()
+            .map(|t| (t.from_stop_id.as_str(), t.to_stop_id.as_str()))
+            .collect();
+        let mut stops: Vec<&Stop> = feed
+            .stops
+            .iter()
+            .filter(|s| s.location_type == LocationType::Stop)
+            .collect();
+        stops.sort_by(|a, b| a.stop_lat.total_cmp(&b.stop_lat));
+
+        // Only stops within the radius by latitude need to be measured
+        let max_lat = latitude_degrees(self.radius);
+        let mut footpaths = Vec::new();
+        for (i, from) in stops.iter().enumerate() {
+            for to in &stops[i + 1..] {
+                if to.stop_lat - from.stop_lat > max_lat {
+                    break;
+                }
+                let distance =
+                    haversine((from.stop_lat, from.stop_lon), (to.stop_lat, to.stop_lon));
+                if distance > self.radius {
+                    continue;
+                }
+                let seconds = (distance / self.walking_speed).ceil() as i64;
+                for (a, b) in [(from, to), (to, from)].iter() {
+                    if !listed.contains(&(a.stop_id.as_str(), b.stop_id.as_str())) {
+                        footpaths.push(Footpath {
+                            from: a,
+                            to: b,
+                            distance,
+                            duration: Duration::seconds(seconds),
+                        });
+                    }
+                }
+            }
+        }
+        footpaths
+    }
+
+    /// The generated footpaths as transfers.txt records, to be added to
+    /// `Feed::transfers` before routing
+    pub fn transfers(&self, feed: &Feed) -> Vec<Transfer> {
+        self.generate(feed)
+            .iter()
+            .map(Footpath::to_transfer)
+            .collect()
+    }
+}
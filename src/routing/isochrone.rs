@@ -0,0 +1,94 @@
+use chrono::Duration;
+
+use crate::distance::{concave_hull, convex_hull};
+use crate::{Stop, TimeOffset};
+
+/// A stop of an `Isochrone`
+#[derive(Debug)]
+pub struct Reachable<'a> {
+    pub stop: &'a Stop,
+    /// Earliest arrival, measured from the queried date
+    pub arrival: TimeOffset,
+    pub travel_time: Duration,
+}
+
+/// Outline drawn around the stops of an `Isochrone`
+#[derive(Clone, Copy, Debug)]
+pub enum Hull {
+    Convex,
+    /// Dents edges more than this many times longer than the distance to
+    /// the nearest stop inside, so smaller values follow the stops closer
+    Concave(f64),
+}
+
+/// Isochrone
+/// The stops reachable from an origin within some travel time, see
+/// `Raptor::isochrone`
+#[derive(Debug)]
+pub struct Isochrone<'a> {
+    pub origin: &'a Stop,
+    pub departure: TimeOffset,
+    /// Including the origin, in the order of the feed
+    pub stops: Vec<Reachable<'a>>,
+}
+
+impl<'a> Isochrone<'a> {
+    pub fn get(&self, stop_id: &str) -> Option<&Reachable<'a>> {
+        self.stops.iter().find(|r| r.stop.stop_id == stop_id)
+    }
+
+    /// (lat, lon) outline of the reachable stops, counterclockwise
+    pub fn hull(&self, hull: Hull) -> Vec<(f64, f64)> {
+        let points: Vec<(f64, f64)> = self
+            .stops
+            .iter()
+            .map(|r| (r.stop.stop_lat, r.stop.stop_lon))
+            .collect();
+        match hull {
+            Hull::Convex => convex_hull(&points),
+            Hull::Concave(concavity) => concave_hull(&points, concavity),
+        }
+    }
+
+    /// GeoJSON FeatureCollection with a point for each reachable stop
+    #[cfg(feature = "geojson")]
+    pub fn to_geojson_points(&self) -> String {
+        use crate::geojson::{feature, feature_collection, point};
+        use serde_json::json;
+
+        let features = self
+            .stops
+            .iter()
+            .map(|r| {
+                let properties = json!({
+                    "stop_id": r.stop.stop_id,
+                    "stop_name": r.stop.stop_name,
+                    "arrival": r.arrival.to_string(),
+                    "travel_time": r.travel_time.num_seconds(),
+                });
+                feature(point((r.stop.stop_lat, r.stop.stop_lon)), properties)
+            })
+            .collect();
+        feature_collection(features)
+    }
+
+    /// GeoJSON FeatureCollection with the outline of the reachable stops as
+    /// a polygon, or as points when there are fewer than three
+    #[cfg(feature = "geojson")]
+    pub fn to_geojson_hull(&self, hull: Hull) -> String {
+        use crate::geojson::{feature, feature_collection, multi_point, polygon};
+        use serde_json::json;
+
+        let ring = self.hull(hull);
+        let geometry = if ring.len() < 3 {
+            multi_point(&ring)
+        } else {
+            polygon(&ring)
+        };
+        let properties = json!({
+            "origin": self.origin.stop_id,
+            "departure": self.departure.to_string(),
+        });
+        feature_collection(vec![feature(geometry, properties)])
+    }
+}
@@ -0,0 +1,250 @@
+//! Journey planning over the trips of a feed running on one date
+mod csa;
+mod footpaths;
+mod isochrone;
+mod raptor;
+
+pub use self::csa::ConnectionScan;
+pub use self::footpaths::{Footpath, FootpathGenerator};
+pub use self::isochrone::{Hull, Isochrone, Reachable};
+pub use self::raptor::Raptor;
+
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDate};
+
+use crate::feed::Feed;
+use crate::intern::{Idx, StopIdx};
+use crate::timetable::ServiceCalendar;
+use crate::{Stop, StopServiceType, StopTime, TimeOffset, Transfer, TransferType, Trip};
+
+/// Seconds since midnight of the day before the queried date, so trips of
+/// the previous service day running past midnight fit in as well
+type Time = u32;
+
+const DAY: Time = 24 * 60 * 60;
+const NEVER: Time = Time::MAX;
+
+fn to_time(offset: &TimeOffset) -> Time {
+    offset.duration().num_seconds() as Time
+}
+
+/// Converts back to an offset from the queried date
+fn to_offset(time: Time) -> TimeOffset {
+    let seconds = time.saturating_sub(DAY);
+    TimeOffset::from_hms(seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+/// One part of a `Journey`
+#[derive(Debug)]
+pub enum Leg<'a> {
+    /// A ride on `trip` from the stop of `from` to the stop of `to`
+    Transit {
+        trip: &'a Trip,
+        service_date: NaiveDate,
+        from: &'a StopTime,
+        to: &'a StopTime,
+        /// Times measured from the queried date
+        departure: TimeOffset,
+        arrival: TimeOffset,
+    },
+    /// A transfer between two stops, or from the origin to a stop
+    Walk {
+        from: &'a Stop,
+        to: &'a Stop,
+        duration: Duration,
+    },
+}
+
+/// A way to get from one stop to another. Times are measured from the
+/// queried date.
+#[derive(Debug)]
+pub struct Journey<'a> {
+    pub departure: TimeOffset,
+    pub arrival: TimeOffset,
+    pub legs: Vec<Leg<'a>>,
+}
+
+impl<'a> Journey<'a> {
+    /// Number of changes between trips
+    pub fn transfers(&self) -> usize {
+        let rides = self
+            .legs
+            .iter()
+            .filter(|leg| matches!(leg, Leg::Transit { .. }))
+            .count();
+        rides.saturating_sub(1)
+    }
+}
+
+/// Stops of a feed by `StopIdx`
+#[derive(Debug)]
+struct Stops<'a> {
+    stops: Vec<&'a Stop>,
+    lookup: HashMap<&'a str, StopIdx>,
+}
+
+impl<'a> Stops<'a> {
+    fn new(feed: &'a Feed) -> Self {
+        let stops: Vec<&Stop> = feed.stops.iter().collect();
+        let lookup = stops
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.stop_id.as_str(), StopIdx::from_index(i)))
+            .collect();
+        Stops { stops, lookup }
+    }
+
+    fn get(&self, stop_id: &str) -> Option<StopIdx> {
+        self.lookup.get(stop_id).copied()
+    }
+
+    fn stop(&self, idx: StopIdx) -> &'a Stop {
+        self.stops[idx.index()]
+    }
+
+    fn len(&self) -> usize {
+        self.stops.len()
+    }
+}
+
+/// Ways to change between trips, from transfers.txt
+#[derive(Clone, Debug)]
+struct Transfers {
+    /// Links to other stops with the time needed to get there
+    footpaths: Vec<Vec<(StopIdx, Time)>>,
+    /// Time needed to change vehicles at each stop, `None` where changing
+    /// is not possible
+    change_times: Vec<Option<Time>>,
+}
+
+impl Transfers {
+    fn new(transfers: &[Transfer], stops: &Stops) -> Self {
+        let mut footpaths = vec![Vec::new(); stops.len()];
+        let mut change_times = vec![Some(0); stops.len()];
+        for transfer in transfers {
+            let (from, to) = match (
+                stops.get(&transfer.from_stop_id),
+                stops.get(&transfer.to_stop_id),
+            ) {
+                (Some(from), Some(to)) => (from, to),
+                _ => continue,
+            };
+            let time = match transfer.transfer_type {
+                TransferType::NotPossible => None,
+                _ => Some(
+                    transfer
+                        .min_transfer_time
+                        .map_or(0, |d| d.num_seconds().max(0) as Time),
+                ),
+            };
+            if from == to {
+                change_times[from.index()] = time;
+            } else if let Some(time) = time {
+                footpaths[from.index()].push((to, time));
+            }
+        }
+        Transfers {
+            footpaths,
+            change_times,
+        }
+    }
+
+    /// A copy with `footpaths` added as walks
+    fn with_footpaths(&self, footpaths: &[Footpath], stops: &Stops) -> Self {
+        let mut transfers = self.clone();
+        for footpath in footpaths {
+            if let (Some(from), Some(to)) = (
+                stops.get(&footpath.from.stop_id),
+                stops.get(&footpath.to.stop_id),
+            ) {
+                let time = footpath.duration.num_seconds().max(0) as Time;
+                transfers.footpaths[from.index()].push((to, time));
+            }
+        }
+        transfers
+    }
+
+    fn footpaths(&self, stop: StopIdx) -> &[(StopIdx, Time)] {
+        &self.footpaths[stop.index()]
+    }
+
+    fn change_time(&self, stop: StopIdx) -> Option<Time> {
+        self.change_times[stop.index()]
+    }
+}
+
+/// A trip running on the queried date with its stop times in order
+#[derive(Debug)]
+struct DayTrip<'a> {
+    trip: &'a Trip,
+    service_date: NaiveDate,
+    stops: Vec<StopIdx>,
+    stop_times: Vec<&'a StopTime>,
+}
+
+impl<'a> DayTrip<'a> {
+    /// Arrival and departure at each stop when queried on `date`
+    fn times(&self, date: NaiveDate) -> Vec<(Time, Time)> {
+        let offset = if self.service_date < date { 0 } else { DAY };
+        self.stop_times
+            .iter()
+            .map(|st| {
+                let arrival = offset + to_time(&st.arrival_time);
+                let departure = offset + to_time(&st.departure_time);
+                (arrival, departure.max(arrival))
+            })
+            .collect()
+    }
+}
+
+fn can_board(stop_time: &StopTime) -> bool {
+    stop_time.pickup_type != StopServiceType::NoServiceAvailable
+}
+
+fn can_alight(stop_time: &StopTime) -> bool {
+    stop_time.dropoff_type != StopServiceType::NoServiceAvailable
+}
+
+/// Trips running on `date`, and those of the day before still running
+/// after midnight. Trips visiting unknown stops are left out.
+fn day_trips<'a>(feed: &'a Feed, stops: &Stops, date: NaiveDate) -> Vec<DayTrip<'a>> {
+    let calendar = ServiceCalendar::new(feed);
+    let mut stop_times: HashMap<&str, Vec<&StopTime>> = HashMap::new();
+    for stop_time in &feed.stop_times {
+        stop_times
+            .entry(stop_time.trip_id.as_str())
+            .or_default()
+            .push(stop_time);
+    }
+
+    let mut trips = Vec::new();
+    for trip in &feed.trips {
+        let mut stop_times = match stop_times.remove(trip.trip_id.as_str()) {
+            Some(stop_times) if stop_times.len() > 1 => stop_times,
+            _ => continue,
+        };
+        stop_times.sort_by_key(|st| st.stop_sequence);
+        let trip_stops: Option<Vec<StopIdx>> =
+            stop_times.iter().map(|st| stops.get(&st.stop_id)).collect();
+        let trip_stops = match trip_stops {
+            Some(trip_stops) => trip_stops,
+            None => continue,
+        };
+        let past_midnight = stop_times
+            .last()
+            .is_some_and(|st| to_time(&st.arrival_time) > DAY);
+        let previous = date.pred_opt().filter(|_| past_midnight);
+        for service_date in [previous, Some(date)].iter().flatten() {
+            if calendar.is_active(&trip.service_id, *service_date) {
+                trips.push(DayTrip {
+                    trip,
+                    service_date: *service_date,
+                    stops: trip_stops.clone(),
+                    stop_times: stop_times.clone(),
+                });
+            }
+        }
+    }
+    trips
+}
@@ -0,0 +1,478 @@
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDate};
+
+use super::isochrone::{Isochrone, Reachable};
+use super::{
+    can_alight, can_board, day_trips, to_offset, to_time, DayTrip, Footpath, Journey, Leg, Stops,
+    Time, Transfers, DAY, NEVER,
+};
+use crate::feed::Feed;
+use crate::intern::{Idx, StopIdx};
+use crate::{StopTime, TimeOffset, Trip};
+
+/// A trip of a `Pattern` with times in `Time`
+#[derive(Debug)]
+struct PatternTrip<'a> {
+    trip: &'a Trip,
+    service_date: NaiveDate,
+    stop_times: Vec<&'a StopTime>,
+    /// Arrival and departure at each stop of the pattern
+    times: Vec<(Time, Time)>,
+}
+
+impl<'a> PatternTrip<'a> {
+    fn new(day_trip: DayTrip<'a>, date: NaiveDate) -> Self {
+        PatternTrip {
+            times: day_trip.times(date),
+            trip: day_trip.trip,
+            service_date: day_trip.service_date,
+            stop_times: day_trip.stop_times,
+        }
+    }
+
+    /// Whether this trip is never earlier than `other` at any stop
+    fn follows(&self, other: &PatternTrip) -> bool {
+        self.times
+            .iter()
+            .zip(&other.times)
+            .all(|(a, b)| a.0 >= b.0 && a.1 >= b.1)
+    }
+}
+
+/// Trips of a route visiting the same stops in the same order, none of
+/// them overtaking another, sorted by departure
+#[derive(Debug)]
+struct Pattern<'a> {
+    stops: Vec<StopIdx>,
+    trips: Vec<PatternTrip<'a>>,
+}
+
+impl<'a> Pattern<'a> {
+    /// The first trip leaving the stop at `position` at or after `time`
+    /// that can be boarded there
+    fn earliest_trip(&self, position: usize, time: Time) -> Option<usize> {
+        let start = self.trips.partition_point(|t| t.times[position].1 < time);
+        (start..self.trips.len()).find(|&t| can_board(self.trips[t].stop_times[position]))
+    }
+}
+
+/// How the arrival at a stop was reached in a round
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Label {
+    None,
+    Origin,
+    Transit {
+        pattern: usize,
+        trip: usize,
+        board: usize,
+        alight: usize,
+    },
+    Walk {
+        from: StopIdx,
+        duration: Time,
+    },
+}
+
+/// Arrivals a search still cares about
+#[derive(Clone, Copy, Debug)]
+struct Limit {
+    target: Option<StopIdx>,
+    deadline: Time,
+}
+
+impl Limit {
+    /// Arrivals must be earlier than this, given the best arrivals so far
+    fn bound(&self, best: &[Time]) -> Time {
+        let target_best = self.target.map_or(NEVER, |t| best[t.index()]);
+        target_best.min(self.deadline.saturating_add(1))
+    }
+}
+
+/// Labels of all stops after a round, which is the number of trips taken
+#[derive(Clone, Debug)]
+struct Round {
+    arrival: Vec<Time>,
+    /// When a trip can be boarded, which is later than the arrival when
+    /// changing vehicles takes time
+    ready: Vec<Time>,
+    /// The round that set `ready`
+    ready_round: Vec<usize>,
+    /// How the arrival was improved in this round, if it was
+    label: Vec<Label>,
+    /// Arrivals by trip in this round, the start of walks
+    by_trip: Vec<Label>,
+    trip_arrival: Vec<Time>,
+}
+
+impl Round {
+    fn new(stops: usize) -> Self {
+        Round {
+            arrival: vec![NEVER; stops],
+            ready: vec![NEVER; stops],
+            ready_round: vec![0; stops],
+            label: vec![Label::None; stops],
+            by_trip: vec![Label::None; stops],
+            trip_arrival: vec![NEVER; stops],
+        }
+    }
+
+    /// The next round starts from the arrivals so far
+    fn next(&self) -> Self {
+        Round {
+            arrival: self.arrival.clone(),
+            ready: self.ready.clone(),
+            ready_round: self.ready_round.clone(),
+            label: vec![Label::None; self.label.len()],
+            by_trip: vec![Label::None; self.label.len()],
+            trip_arrival: vec![NEVER; self.label.len()],
+        }
+    }
+}
+
+/// Raptor
+/// Round-based public transit routing (Delling et al.) over the trips of a
+/// feed running on one date. Each round allows one more trip, giving the
+/// earliest arrival for each number of transfers.
+#[derive(Debug)]
+pub struct Raptor<'a> {
+    stops: Stops<'a>,
+    transfers: Transfers,
+    patterns: Vec<Pattern<'a>>,
+    /// Patterns through each stop with the stop's position in them
+    stop_patterns: Vec<Vec<(usize, usize)>>,
+    /// Most transfers a journey may have
+    pub max_transfers: usize,
+}
+
+impl<'a> Raptor<'a> {
+    pub fn new(feed: &'a Feed, date: NaiveDate) -> Self {
+        let stops = Stops::new(feed);
+        let transfers = Transfers::new(&feed.transfers, &stops);
+
+        let mut by_stops: HashMap<(&str, Vec<StopIdx>), Vec<PatternTrip>> = HashMap::new();
+        for day_trip in day_trips(feed, &stops, date) {
+            let key = (day_trip.trip.route_id.as_str(), day_trip.stops.clone());
+            by_stops
+                .entry(key)
+                .or_default()
+                .push(PatternTrip::new(day_trip, date));
+        }
+        let mut patterns: Vec<Pattern> = Vec::new();
+        for ((_, pattern_stops), mut trips) in by_stops {
+            trips.sort_by_key(|t| t.times[0].1);
+            let first = patterns.len();
+            for trip in trips {
+                let fits = patterns[first..]
+                    .iter()
+                    .position(|p| p.trips.last().is_some_and(|last| trip.follows(last)));
+                match fits {
+                    Some(i) => patterns[first + i].trips.push(trip),
+                    None => patterns.push(Pattern {
+                        stops: pattern_stops.clone(),
+                        trips: vec![trip],
+                    }),
+                }
+            }
+        }
+
+        let mut stop_patterns = vec![Vec::new(); stops.len()];
+        for (p, pattern) in patterns.iter().enumerate() {
+            for (position, stop) in pattern.stops.iter().enumerate() {
+                stop_patterns[stop.index()].push((p, position));
+            }
+        }
+
+        Raptor {
+            stops,
+            transfers,
+            patterns,
+            stop_patterns,
+            max_transfers: 8,
+        }
+    }
+
+    /// The journey arriving at `to` first when leaving `from` at
+    /// `departure`, with the fewest transfers among those
+    pub fn earliest_arrival(
+        &self,
+        from: &str,
+        to: &str,
+        departure: TimeOffset,
+    ) -> Option<Journey<'a>> {
+        self.pareto(from, to, departure).pop()
+    }
+
+    /// Journeys from `from` to `to` leaving at `departure` that are not
+    /// beaten on both arrival and transfers, ordered by transfers
+    pub fn pareto(&self, from: &str, to: &str, departure: TimeOffset) -> Vec<Journey<'a>> {
+        let (origin, target) = match (self.stops.get(from), self.stops.get(to)) {
+            (Some(origin), Some(target)) => (origin, target),
+            _ => return Vec::new(),
+        };
+        let departure = DAY + to_time(&departure);
+        let rounds = self.run(
+            &self.transfers,
+            origin,
+            departure,
+            Limit {
+                target: Some(target),
+                deadline: NEVER,
+            },
+        );
+        rounds
+            .iter()
+            .enumerate()
+            .filter(|(_, round)| round.label[target.index()] != Label::None)
+            .map(|(k, _)| self.journey(&rounds, k, target, departure))
+            .collect()
+    }
+
+    /// Stops reachable from `from` within `max_travel` when leaving at
+    /// `departure`, with the earliest arrival at each. Walks follow
+    /// transfers.txt and `footpaths`, such as those of a
+    /// `FootpathGenerator`.
+    pub fn isochrone(
+        &self,
+        from: &str,
+        departure: TimeOffset,
+        max_travel: Duration,
+        footpaths: &[Footpath],
+    ) -> Option<Isochrone<'a>> {
+        let origin = self.stops.get(from)?;
+        let start = DAY + to_time(&departure);
+        let deadline = start.saturating_add(max_travel.num_seconds().max(0) as Time);
+        let transfers = self.transfers.with_footpaths(footpaths, &self.stops);
+        let rounds = self.run(
+            &transfers,
+            origin,
+            start,
+            Limit {
+                target: None,
+                deadline,
+            },
+        );
+        let arrivals = &rounds.last()?.arrival;
+        let stops = arrivals
+            .iter()
+            .enumerate()
+            .filter(|(_, arrival)| **arrival <= deadline)
+            .map(|(i, arrival)| Reachable {
+                stop: self.stops.stop(StopIdx::from_index(i)),
+                arrival: to_offset(*arrival),
+                travel_time: Duration::seconds(i64::from(arrival - start)),
+            })
+            .collect();
+        Some(Isochrone {
+            origin: self.stops.stop(origin),
+            departure,
+            stops,
+        })
+    }
+
+    /// Rounds of the search from `origin`, leaving out arrivals beyond
+    /// `limit`
+    fn run(
+        &self,
+        transfers: &Transfers,
+        origin: StopIdx,
+        departure: Time,
+        limit: Limit,
+    ) -> Vec<Round> {
+        let stop_count = self.stops.len();
+        let mut best = vec![NEVER; stop_count];
+        // Walks may only follow trips, so arrivals by trip are kept apart
+        let mut best_by_trip = vec![NEVER; stop_count];
+        let mut first = Round::new(stop_count);
+        first.arrival[origin.index()] = departure;
+        first.ready[origin.index()] = departure;
+        first.label[origin.index()] = Label::Origin;
+        best[origin.index()] = departure;
+        let mut marked = vec![origin];
+        let sources = [(origin, departure)];
+        walk(
+            transfers,
+            &mut first,
+            &mut best,
+            &mut marked,
+            &sources,
+            limit,
+            0,
+        );
+        let mut rounds = vec![first];
+
+        for k in 1..=self.max_transfers + 1 {
+            if marked.is_empty() {
+                break;
+            }
+            let mut round = rounds[k - 1].next();
+            let previous = &rounds[k - 1];
+
+            // Scan each pattern from the earliest stop marked on it
+            let mut queue: HashMap<usize, usize> = HashMap::new();
+            for stop in marked.drain(..) {
+                for &(pattern, position) in &self.stop_patterns[stop.index()] {
+                    let start = queue.entry(pattern).or_insert(position);
+                    *start = (*start).min(position);
+                }
+            }
+            let mut walkers = Vec::new();
+            for (p, start) in queue {
+                let pattern = &self.patterns[p];
+                let mut current: Option<(usize, usize)> = None;
+                for (i, &stop) in pattern.stops.iter().enumerate().skip(start) {
+                    if let Some((t, board)) = current {
+                        let trip = &pattern.trips[t];
+                        let arrival = trip.times[i].0;
+                        let bound = best_by_trip[stop.index()].min(limit.bound(&best));
+                        if can_alight(trip.stop_times[i]) && arrival < bound {
+                            let label = Label::Transit {
+                                pattern: p,
+                                trip: t,
+                                board,
+                                alight: i,
+                            };
+                            round.by_trip[stop.index()] = label;
+                            round.trip_arrival[stop.index()] = arrival;
+                            best_by_trip[stop.index()] = arrival;
+                            walkers.push(stop);
+                            if arrival < best[stop.index()] {
+                                round.arrival[stop.index()] = arrival;
+                                round.label[stop.index()] = label;
+                                best[stop.index()] = arrival;
+                                marked.push(stop);
+                            }
+                        }
+                    }
+                    let ready = previous.ready[stop.index()];
+                    let catchable = match current {
+                        Some((t, _)) => ready <= pattern.trips[t].times[i].1,
+                        None => ready != NEVER,
+                    };
+                    if catchable {
+                        match (pattern.earliest_trip(i, ready), current) {
+                            (Some(t), Some((c, _))) if t >= c => (),
+                            (Some(t), _) => current = Some((t, i)),
+                            (None, _) => (),
+                        }
+                    }
+                }
+            }
+
+            marked.sort();
+            marked.dedup();
+            for &stop in &marked {
+                let arrival = round.arrival[stop.index()];
+                if let Some(change) = transfers.change_time(stop) {
+                    if arrival + change < round.ready[stop.index()] {
+                        round.ready[stop.index()] = arrival + change;
+                        round.ready_round[stop.index()] = k;
+                    }
+                }
+            }
+            walkers.sort();
+            walkers.dedup();
+            let sources: Vec<_> = walkers
+                .into_iter()
+                .map(|stop| (stop, round.trip_arrival[stop.index()]))
+                .collect();
+            walk(
+                transfers,
+                &mut round,
+                &mut best,
+                &mut marked,
+                &sources,
+                limit,
+                k,
+            );
+            rounds.push(round);
+        }
+        rounds
+    }
+
+    fn journey(&self, rounds: &[Round], k: usize, target: StopIdx, departure: Time) -> Journey<'a> {
+        let arrival = rounds[k].arrival[target.index()];
+        let mut legs = Vec::new();
+        let mut k = k;
+        let mut stop = target;
+        let mut label = rounds[k].label[stop.index()];
+        loop {
+            match label {
+                Label::Walk { from, duration } => {
+                    legs.push(Leg::Walk {
+                        from: self.stops.stop(from),
+                        to: self.stops.stop(stop),
+                        duration: Duration::seconds(i64::from(duration)),
+                    });
+                    label = match k {
+                        0 => Label::Origin,
+                        _ => rounds[k].by_trip[from.index()],
+                    };
+                    stop = from;
+                }
+                Label::Transit {
+                    pattern,
+                    trip,
+                    board,
+                    alight,
+                } => {
+                    let pattern = &self.patterns[pattern];
+                    let trip = &pattern.trips[trip];
+                    legs.push(Leg::Transit {
+                        trip: trip.trip,
+                        service_date: trip.service_date,
+                        from: trip.stop_times[board],
+                        to: trip.stop_times[alight],
+                        departure: to_offset(trip.times[board].1),
+                        arrival: to_offset(trip.times[alight].0),
+                    });
+                    stop = pattern.stops[board];
+                    k = rounds[k - 1].ready_round[stop.index()];
+                    label = rounds[k].label[stop.index()];
+                }
+                Label::Origin | Label::None => break,
+            }
+        }
+        legs.reverse();
+
+        // Leave as late as the first trip allows
+        let departure = match (legs.first(), legs.get(1)) {
+            (Some(Leg::Transit { departure, .. }), _) => departure.clone(),
+            (Some(Leg::Walk { duration, .. }), Some(Leg::Transit { departure, .. })) => {
+                to_offset(DAY + to_time(departure) - duration.num_seconds() as Time)
+            }
+            _ => to_offset(departure),
+        };
+        Journey {
+            departure,
+            arrival: to_offset(arrival),
+            legs,
+        }
+    }
+}
+
+/// Follows footpaths from `sources`, reached at the given times in round
+/// `k`
+fn walk(
+    transfers: &Transfers,
+    round: &mut Round,
+    best: &mut [Time],
+    marked: &mut Vec<StopIdx>,
+    sources: &[(StopIdx, Time)],
+    limit: Limit,
+    k: usize,
+) {
+    for &(from, start) in sources {
+        for &(to, duration) in transfers.footpaths(from) {
+            let arrival = start + duration;
+            if arrival < best[to.index()].min(limit.bound(best)) {
+                round.arrival[to.index()] = arrival;
+                round.ready[to.index()] = arrival;
+                round.ready_round[to.index()] = k;
+                round.label[to.index()] = Label::Walk { from, duration };
+                best[to.index()] = arrival;
+                marked.push(to);
+            }
+        }
+    }
+}
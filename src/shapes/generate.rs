@@ -0,0 +1,168 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::distance::haversine;
+use crate::feed::Feed;
+use crate::{ShapePoint, StopTime};
+
+/// A shape made up by `ShapeGenerator`, shared by trips visiting the same
+/// stops in the same order
+#[derive(Debug)]
+pub struct GeneratedShape {
+    pub shape_id: String,
+    /// A point at each stop, with shape_dist_traveled in meters
+    pub points: Vec<ShapePoint>,
+    pub trip_ids: Vec<String>,
+}
+
+/// ShapeGenerator
+/// Makes up straight line shapes through the stops of trips without a
+/// shape, as some consumers require them
+#[derive(Debug)]
+pub struct ShapeGenerator {
+    /// Start of the generated shape_ids, which are numbered from 1 skipping
+    /// any already in the feed
+    pub prefix: String,
+}
+
+impl Default for ShapeGenerator {
+    fn default() -> Self {
+        ShapeGenerator {
+            prefix: "generated_".to_string(),
+        }
+    }
+}
+
+impl ShapeGenerator {
+    pub fn new(prefix: &str) -> Self {
+        ShapeGenerator {
+            prefix: prefix.to_string(),
+        }
+    }
+
+    /// Shapes for the trips of `feed` with no shape_id or one missing from
+    /// shapes.txt. Trips with fewer than two stops or visiting unknown
+    /// stops are left out.
+    pub fn generate(&self, feed: &Feed) -> Vec<GeneratedShape> {
+        let shape_ids: HashSet<&str> = feed.shapes.iter().map(|p| p.shape_id.as_str()).collect();
+        let stops: HashMap<&str, (f64, f64)> = feed
+            .stops
+            .iter()
+            .map(|s| (s.stop_id.as_str(), (s.stop_lat, s.stop_lon)))
+            .collect();
+        let mut stop_times: HashMap<&str, Vec<&StopTime>> = HashMap::new();
+        for stop_time in &feed.stop_times {
+            stop_times
+                .entry(stop_time.trip_id.as_str())
+                .or_default()
+                .push(stop_time);
+        }
+
+        let mut generated: Vec<GeneratedShape> = Vec::new();
+        let mut patterns: HashMap<Vec<&str>, usize> = HashMap::new();
+        let mut next_id = 1;
+        for trip in &feed.trips {
+            if trip
+                .shape_id
+                .as_deref()
+                .is_some_and(|id| shape_ids.contains(id))
+            {
+                continue;
+            }
+            let mut stop_times = match stop_times.remove(trip.trip_id.as_str()) {
+                Some(stop_times) if stop_times.len() > 1 => stop_times,
+                _ => continue,
+            };
+            stop_times.sort_by_key(|st| st.stop_sequence);
+            let pattern: Vec<&str> = stop_times.iter().map(|st| st.stop_id.as_str()).collect();
+            if let Some(&i) = patterns.get(&pattern) {
+                generated[i].trip_ids.push(trip.trip_id.clone());
+                continue;
+            }
+            let positions: Option<Vec<(f64, f64)>> =
+                pattern.iter().map(|id| stops.get(id).copied()).collect();
+            let positions = match positions {
+                Some(positions) => positions,
+                None => continue,
+            };
+
+            let shape_id = loop {
+                let shape_id = format!("{}{}", self.prefix, next_id);
+                next_id += 1;
+                if !shape_ids.contains(shape_id.as_str()) {
+                    break shape_id;
+                }
+            };
+            let mut distance = 0.0;
+            let points = positions
+                .iter()
+                .enumerate()
+                .map(|(i, &(lat, lon))| {
+                    if i > 0 {
+                        distance += haversine(positions[i - 1], (lat, lon));
+                    }
+                    ShapePoint {
+                        shape_id: shape_id.clone(),
+                        shape_pt_lat: lat,
+                        shape_pt_lon: lon,
+                        shape_pt_sequence: i as u64 + 1,
+                        shape_dist_traveled: Some(distance),
+                    }
+                })
+                .collect();
+            patterns.insert(pattern, generated.len());
+            generated.push(GeneratedShape {
+                shape_id,
+                points,
+                trip_ids: vec![trip.trip_id.clone()],
+            });
+        }
+        generated
+    }
+
+    /// Adds the generated shapes to `feed`, assigning them to their trips
+    /// and setting shape_dist_traveled of the trips' stop times to the
+    /// distance along the shape. Returns the number of shapes added.
+    pub fn apply(&self, feed: &mut Feed) -> usize {
+        let generated = self.generate(feed);
+        let mut distances: HashMap<&str, Vec<f64>> = HashMap::new();
+        let mut assigned: HashMap<&str, &str> = HashMap::new();
+        for shape in &generated {
+            for trip_id in &shape.trip_ids {
+                assigned.insert(trip_id, &shape.shape_id);
+                let along = shape
+                    .points
+                    .iter()
+                    .filter_map(|p| p.shape_dist_traveled)
+                    .collect();
+                distances.insert(trip_id, along);
+            }
+        }
+
+        for trip in feed.trips.iter_mut() {
+            if let Some(shape_id) = assigned.get(trip.trip_id.as_str()) {
+                trip.shape_id = Some(shape_id.to_string());
+            }
+        }
+        // Stop times of a trip get the distances in stop_sequence order
+        let mut order: Vec<usize> = (0..feed.stop_times.len()).collect();
+        order.sort_by_key(|&i| feed.stop_times[i].stop_sequence);
+        let mut seen: HashMap<&str, usize> = HashMap::new();
+        let mut updates = Vec::new();
+        for i in order {
+            let trip_id = feed.stop_times[i].trip_id.as_str();
+            if let Some(along) = distances.get(trip_id) {
+                let position = seen.entry(trip_id).or_default();
+                updates.push((i, along[*position]));
+                *position += 1;
+            }
+        }
+        for (i, distance) in updates {
+            feed.stop_times[i].shape_dist_traveled = Some(distance);
+        }
+
+        let count = generated.len();
+        feed.shapes
+            .extend(generated.into_iter().flat_map(|shape| shape.points));
+        count
+    }
+}
@@ -0,0 +1,10 @@
+//! Utilities for shapes.txt
+mod generate;
+mod project;
+mod simplify;
+
+pub use self::generate::{GeneratedShape, ShapeGenerator};
+pub use self::project::{
+    shape_distances, ProjectionProblem, ShapeProjector, StopProjection, TripProjection,
+};
+pub use self::simplify::{densify, densify_shapes, simplify, simplify_shapes, write_shapes};
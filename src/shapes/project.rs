@@ -0,0 +1,306 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::distance::{haversine, project_onto_segment};
+use crate::feed::Feed;
+use crate::{ShapePoint, StopTime, Trip};
+
+/// Added to the cost of stops projected before the previous stop of the
+/// trip, so the fewest stops end up out of order
+const OUT_OF_ORDER_PENALTY: f64 = 1e9;
+
+/// Something wrong with where a stop lies relative to the shape
+#[derive(Debug, PartialEq)]
+pub enum ProjectionProblem {
+    /// Further than `ShapeProjector::max_distance` from the shape
+    TooFar,
+    /// The stop lies on the shape before the previous stop of the trip
+    OutOfOrder,
+}
+
+/// Where a stop of a trip lies along its shape
+#[derive(Debug)]
+pub struct StopProjection<'a> {
+    pub stop_time: &'a StopTime,
+    /// Meters along the shape, never less than that of the previous stop
+    pub shape_dist_traveled: f64,
+    /// Meters from the stop to the shape
+    pub distance: f64,
+    pub problem: Option<ProjectionProblem>,
+}
+
+/// The stops of a trip projected onto its shape
+#[derive(Debug)]
+pub struct TripProjection<'a> {
+    pub trip: &'a Trip,
+    /// In stop_sequence order
+    pub stops: Vec<StopProjection<'a>>,
+}
+
+impl<'a> TripProjection<'a> {
+    pub fn problems(&self) -> impl Iterator<Item = &StopProjection<'a>> {
+        self.stops.iter().filter(|s| s.problem.is_some())
+    }
+}
+
+/// Meters along the shape at each of its `points`
+pub fn shape_distances(points: &[&ShapePoint]) -> Vec<f64> {
+    let mut distance = 0.0;
+    let mut distances = Vec::with_capacity(points.len());
+    for (i, point) in points.iter().enumerate() {
+        if i > 0 {
+            let previous = points[i - 1];
+            distance += haversine(
+                (previous.shape_pt_lat, previous.shape_pt_lon),
+                (point.shape_pt_lat, point.shape_pt_lon),
+            );
+        }
+        distances.push(distance);
+    }
+    distances
+}
+
+/// A place on the shape a stop could be at
+#[derive(Clone, Copy, Debug)]
+struct Candidate {
+    along: f64,
+    distance: f64,
+}
+
+/// ShapeProjector
+/// Computes shape_dist_traveled of stop times by projecting the stops of
+/// each trip onto its shape in order. Shapes passing a stop several times,
+/// as loops do, are handled by choosing the projections that keep the
+/// stops in order while staying closest to the shape overall.
+#[derive(Debug)]
+pub struct ShapeProjector {
+    /// Meters from the shape beyond which a stop is flagged
+    pub max_distance: f64,
+}
+
+impl Default for ShapeProjector {
+    fn default() -> Self {
+        ShapeProjector {
+            max_distance: 100.0,
+        }
+    }
+}
+
+impl ShapeProjector {
+    pub fn new(max_distance: f64) -> Self {
+        ShapeProjector { max_distance }
+    }
+
+    /// Projections of the trips of `feed` having a shape with at least two
+    /// points. Trips visiting unknown stops are left out.
+    pub fn project<'a>(&self, feed: &'a Feed) -> Vec<TripProjection<'a>> {
+        let shapes = feed.shapes_by_id();
+        let stops: HashMap<&str, (f64, f64)> = feed
+            .stops
+            .iter()
+            .map(|s| (s.stop_id.as_str(), (s.stop_lat, s.stop_lon)))
+            .collect();
+        let mut stop_times: HashMap<&str, Vec<&StopTime>> = HashMap::new();
+        for stop_time in &feed.stop_times {
+            stop_times
+                .entry(stop_time.trip_id.as_str())
+                .or_default()
+                .push(stop_time);
+        }
+
+        let mut projections = Vec::new();
+        for trip in &feed.trips {
+            let points = match trip.shape_id.as_deref().and_then(|id| shapes.get(id)) {
+                Some(points) if points.len() > 1 => points,
+                _ => continue,
+            };
+            let mut stop_times = match stop_times.remove(trip.trip_id.as_str()) {
+                Some(stop_times) => stop_times,
+                None => continue,
+            };
+            stop_times.sort_by_key(|st| st.stop_sequence);
+            let positions: Option<Vec<(f64, f64)>> = stop_times
+                .iter()
+                .map(|st| stops.get(st.stop_id.as_str()).copied())
+                .collect();
+            if let Some(positions) = positions {
+                projections.push(self.project_trip(trip, &stop_times, &positions, points));
+            }
+        }
+        projections
+    }
+
+    /// Sets shape_dist_traveled in meters for the points of every shape
+    /// used by a projected trip and for the stop times of those trips.
+    /// Other trips on those shapes cannot be projected, so their stop times
+    /// lose shape_dist_traveled rather than keep it in other units.
+    /// Returns the projections' problems as (trip_id, stop_sequence,
+    /// problem).
+    pub fn apply(&self, feed: &mut Feed) -> Vec<(String, u64, ProjectionProblem)> {
+        let mut problems = Vec::new();
+        let mut distances: HashMap<(String, u64), f64> = HashMap::new();
+        let mut shape_ids: HashSet<String> = HashSet::new();
+        let mut projected: HashSet<String> = HashSet::new();
+        for projection in self.project(feed) {
+            shape_ids.extend(projection.trip.shape_id.clone());
+            projected.insert(projection.trip.trip_id.clone());
+            for stop in projection.stops {
+                let key = (
+                    projection.trip.trip_id.clone(),
+                    stop.stop_time.stop_sequence,
+                );
+                if let Some(problem) = stop.problem {
+                    problems.push((key.0.clone(), key.1, problem));
+                }
+                distances.insert(key, stop.shape_dist_traveled);
+            }
+        }
+
+        let mut along: HashMap<(String, u64), f64> = HashMap::new();
+        for (shape_id, points) in feed.shapes_by_id() {
+            if shape_ids.contains(shape_id) {
+                for (point, distance) in points.iter().zip(shape_distances(&points)) {
+                    along.insert((shape_id.to_string(), point.shape_pt_sequence), distance);
+                }
+            }
+        }
+        for point in feed.shapes.iter_mut() {
+            let key = (point.shape_id.clone(), point.shape_pt_sequence);
+            if let Some(distance) = along.get(&key) {
+                point.shape_dist_traveled = Some(*distance);
+            }
+        }
+        let skipped: HashSet<&str> = feed
+            .trips
+            .iter()
+            .filter(|t| !projected.contains(&t.trip_id))
+            .filter(|t| t.shape_id.as_ref().is_some_and(|id| shape_ids.contains(id)))
+            .map(|t| t.trip_id.as_str())
+            .collect();
+        for stop_time in feed.stop_times.iter_mut() {
+            let key = (stop_time.trip_id.clone(), stop_time.stop_sequence);
+            if let Some(distance) = distances.get(&key) {
+                stop_time.shape_dist_traveled = Some(*distance);
+            } else if skipped.contains(stop_time.trip_id.as_str()) {
+                stop_time.shape_dist_traveled = None;
+            }
+        }
+        problems
+    }
+
+    fn project_trip<'a>(
+        &self,
+        trip: &'a Trip,
+        stop_times: &[&'a StopTime],
+        positions: &[(f64, f64)],
+        points: &[&ShapePoint],
+    ) -> TripProjection<'a> {
+        let shape: Vec<(f64, f64)> = points
+            .iter()
+            .map(|p| (p.shape_pt_lat, p.shape_pt_lon))
+            .collect();
+        let along = shape_distances(points);
+        let candidates: Vec<Vec<Candidate>> = positions
+            .iter()
+            .map(|p| self.candidates(*p, &shape, &along))
+            .collect();
+
+        // Cheapest total distance to the shape for each candidate of each
+        // stop, with the candidate of the previous stop it follows
+        let mut costs: Vec<Vec<(f64, usize)>> = Vec::with_capacity(candidates.len());
+        for (i, stop_candidates) in candidates.iter().enumerate() {
+            let row = stop_candidates
+                .iter()
+                .map(|c| {
+                    if i == 0 {
+                        return (c.distance, 0);
+                    }
+                    candidates[i - 1]
+                        .iter()
+                        .zip(&costs[i - 1])
+                        .enumerate()
+                        .map(|(j, (previous, (cost, _)))| {
+                            let penalty = if c.along < previous.along {
+                                OUT_OF_ORDER_PENALTY
+                            } else {
+                                0.0
+                            };
+                            (cost + penalty + c.distance, j)
+                        })
+                        .min_by(|a, b| a.0.total_cmp(&b.0))
+                        .unwrap_or((c.distance, 0))
+                })
+                .collect();
+            costs.push(row);
+        }
+
+        let mut chosen = vec![0; candidates.len()];
+        if let Some(last) = costs.last() {
+            let mut best = (0..last.len())
+                .min_by(|&a, &b| last[a].0.total_cmp(&last[b].0))
+                .unwrap_or(0);
+            for i in (0..costs.len()).rev() {
+                chosen[i] = best;
+                best = costs[i][best].1;
+            }
+        }
+
+        let mut stops = Vec::with_capacity(stop_times.len());
+        let mut previous = 0.0;
+        for (i, stop_time) in stop_times.iter().enumerate() {
+            let candidate = candidates[i][chosen[i]];
+            let problem = if candidate.distance > self.max_distance {
+                Some(ProjectionProblem::TooFar)
+            } else if candidate.along < previous {
+                Some(ProjectionProblem::OutOfOrder)
+            } else {
+                None
+            };
+            previous = candidate.along.max(previous);
+            stops.push(StopProjection {
+                stop_time,
+                shape_dist_traveled: previous,
+                distance: candidate.distance,
+                problem,
+            });
+        }
+        TripProjection { trip, stops }
+    }
+
+    /// The closest point of each segment of the shape within
+    /// `max_distance` of `position`, or the closest point overall when
+    /// there is none
+    fn candidates(
+        &self,
+        position: (f64, f64),
+        shape: &[(f64, f64)],
+        along: &[f64],
+    ) -> Vec<Candidate> {
+        let segments = shape
+            .windows(2)
+            .zip(along.windows(2))
+            .map(|(points, along)| {
+                let (t, distance) = project_onto_segment(position, points[0], points[1]);
+                Candidate {
+                    along: along[0] + t * (along[1] - along[0]),
+                    distance,
+                }
+            });
+        let mut closest: Option<Candidate> = None;
+        let mut candidates: Vec<Candidate> = Vec::new();
+        for candidate in segments {
+            if closest.is_none_or(|c| candidate.distance < c.distance) {
+                closest = Some(candidate);
+            }
+            // Segments meeting at the closest point give it twice
+            if candidate.distance <= self.max_distance
+                && candidates.last().is_none_or(|c| c.along != candidate.along)
+            {
+                candidates.push(candidate);
+            }
+        }
+        if candidates.is_empty() {
+            candidates.extend(closest);
+        }
+        candidates
+    }
+}
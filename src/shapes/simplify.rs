@@ -0,0 +1,106 @@
+use std::io::Write;
+
+use crate::distance::{haversine, project_onto_segment};
+use crate::feed::Feed;
+use crate::{Error, ShapePoint};
+
+fn position(point: &ShapePoint) -> (f64, f64) {
+    (point.shape_pt_lat, point.shape_pt_lon)
+}
+
+/// Douglas–Peucker simplification of the ordered `points` of a shape,
+/// keeping the points needed for the line to stay within `tolerance`
+/// meters of the original. Kept points are unchanged, so
+/// shape_dist_traveled of stop times still interpolates along them.
+pub fn simplify(points: &[&ShapePoint], tolerance: f64) -> Vec<ShapePoint> {
+    if points.len() < 3 {
+        return points.iter().map(|p| (*p).clone()).collect();
+    }
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    let mut ranges = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = ranges.pop() {
+        let (a, b) = (position(points[first]), position(points[last]));
+        let furthest = (first + 1..last)
+            .map(|i| (i, project_onto_segment(position(points[i]), a, b).1))
+            .max_by(|x, y| x.1.total_cmp(&y.1));
+        if let Some((i, distance)) = furthest {
+            if distance > tolerance {
+                keep[i] = true;
+                ranges.push((first, i));
+                ranges.push((i, last));
+            }
+        }
+    }
+    points
+        .iter()
+        .zip(keep)
+        .filter(|(_, keep)| *keep)
+        .map(|(p, _)| (*p).clone())
+        .collect()
+}
+
+/// Adds points to the ordered `points` of a shape so no segment is longer
+/// than `max_length` meters. Added points are spread evenly over each
+/// segment, with shape_dist_traveled interpolated where both ends have it.
+/// Points are numbered again from 1.
+pub fn densify(points: &[&ShapePoint], max_length: f64) -> Vec<ShapePoint> {
+    let mut dense: Vec<ShapePoint> = Vec::with_capacity(points.len());
+    for (i, point) in points.iter().enumerate() {
+        if i > 0 && max_length > 0.0 {
+            let previous = points[i - 1];
+            let length = haversine(position(previous), position(point));
+            let pieces = (length / max_length).ceil() as usize;
+            for k in 1..pieces {
+                let f = k as f64 / pieces as f64;
+                let interpolate = |from: f64, to: f64| from + f * (to - from);
+                dense.push(ShapePoint {
+                    shape_id: point.shape_id.clone(),
+                    shape_pt_lat: interpolate(previous.shape_pt_lat, point.shape_pt_lat),
+                    shape_pt_lon: interpolate(previous.shape_pt_lon, point.shape_pt_lon),
+                    shape_pt_sequence: 0,
+                    shape_dist_traveled: previous
+                        .shape_dist_traveled
+                        .zip(point.shape_dist_traveled)
+                        .map(|(from, to)| interpolate(from, to)),
+                });
+            }
+        }
+        dense.push((*point).clone());
+    }
+    for (i, point) in dense.iter_mut().enumerate() {
+        point.shape_pt_sequence = i as u64 + 1;
+    }
+    dense
+}
+
+/// Replaces every shape of `feed` with `f` applied to its ordered points.
+/// Shapes come out ordered by shape_id.
+fn map_shapes(feed: &mut Feed, f: impl Fn(&[&ShapePoint]) -> Vec<ShapePoint>) {
+    let mut shapes: Vec<_> = feed.shapes_by_id().into_iter().collect();
+    shapes.sort_by_key(|(shape_id, _)| *shape_id);
+    let points = shapes.iter().flat_map(|(_, points)| f(points)).collect();
+    feed.shapes = points;
+}
+
+/// Simplifies every shape of `feed`, see `simplify`
+pub fn simplify_shapes(feed: &mut Feed, tolerance: f64) {
+    map_shapes(feed, |points| simplify(points, tolerance));
+}
+
+/// Densifies every shape of `feed`, see `densify`
+pub fn densify_shapes(feed: &mut Feed, max_length: f64) {
+    map_shapes(feed, |points| densify(points, max_length));
+}
+
+/// Writes `points` as shapes.txt
+pub fn write_shapes<W: Write>(writer: W, points: &[ShapePoint]) -> Result<(), Error> {
+    let mut writer = csv::Writer::from_writer(writer);
+    for point in points {
+        writer
+            .serialize(point)
+            .map_err(|e| Error::Csv("shapes.txt".to_string(), e))?;
+    }
+    writer.flush().map_err(|e| Error::Feed(format!("{}", e)))
+}
@@ -0,0 +1,408 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+use std::ops::Range;
+
+use crate::distance::{haversine, EARTH_RADIUS};
+use crate::feed::Feed;
+use crate::{LocationType, Stop, WheelchairAccessible};
+
+/// Children per node of the tree
+const NODE_SIZE: usize = 16;
+
+/// A (lat, lon) rectangle not crossing the antimeridian
+#[derive(Clone, Copy, Debug)]
+struct BBox {
+    min: (f64, f64),
+    max: (f64, f64),
+}
+
+impl BBox {
+    fn point(p: (f64, f64)) -> Self {
+        BBox { min: p, max: p }
+    }
+
+    fn union(&self, other: &BBox) -> BBox {
+        BBox {
+            min: (self.min.0.min(other.min.0), self.min.1.min(other.min.1)),
+            max: (self.max.0.max(other.max.0), self.max.1.max(other.max.1)),
+        }
+    }
+
+    fn intersects(&self, other: &BBox) -> bool {
+        self.min.0 <= other.max.0
+            && other.min.0 <= self.max.0
+            && self.min.1 <= other.max.1
+            && other.min.1 <= self.max.1
+    }
+
+    fn contains(&self, p: (f64, f64)) -> bool {
+        self.intersects(&BBox::point(p))
+    }
+
+    /// A lower bound in meters on the distance from `p` to any point inside
+    fn min_distance(&self, p: (f64, f64)) -> f64 {
+        let (lat, lon) = p;
+        if lon >= self.min.1 && lon <= self.max.1 {
+            return haversine(p, (lat.clamp(self.min.0, self.max.0), lon));
+        }
+        // Otherwise the closest point lies on the nearer meridian edge
+        let delta = |edge: f64| {
+            let d = (lon - edge).rem_euclid(360.0);
+            d.min(360.0 - d)
+        };
+        let edge = if delta(self.min.1) <= delta(self.max.1) {
+            self.min.1
+        } else {
+            self.max.1
+        };
+        let cos = delta(edge).to_radians().cos();
+        if cos <= 0.0 {
+            let gap = lat - lat.clamp(self.min.0, self.max.0);
+            return gap.abs().to_radians() * EARTH_RADIUS;
+        }
+        let closest = (lat.to_radians().tan() / cos).atan().to_degrees();
+        haversine(p, (closest.clamp(self.min.0, self.max.0), edge))
+    }
+}
+
+/// StopFilter
+/// Which stops a `StopIndex` query returns. The default matches every stop.
+#[derive(Debug, Default)]
+pub struct StopFilter {
+    /// Any location type when empty
+    pub location_types: Vec<LocationType>,
+    /// Child stops without information take that of their parent station
+    pub wheelchair_boarding: Option<WheelchairAccessible>,
+}
+
+impl StopFilter {
+    pub fn location_type(mut self, location_type: LocationType) -> Self {
+        self.location_types.push(location_type);
+        self
+    }
+
+    pub fn wheelchair_boarding(mut self, wheelchair_boarding: WheelchairAccessible) -> Self {
+        self.wheelchair_boarding = Some(wheelchair_boarding);
+        self
+    }
+
+    fn matches(&self, entry: &Entry) -> bool {
+        (self.location_types.is_empty() || self.location_types.contains(&entry.stop.location_type))
+            && self
+                .wheelchair_boarding
+                .as_ref()
+                .is_none_or(|w| w == entry.wheelchair_boarding)
+    }
+}
+
+#[derive(Debug)]
+struct Entry<'a> {
+    stop: &'a Stop,
+    position: (f64, f64),
+    wheelchair_boarding: &'a WheelchairAccessible,
+}
+
+#[derive(Debug)]
+struct Node {
+    bbox: BBox,
+    /// Nodes of the level below, or entries for the lowest level
+    children: Range<usize>,
+}
+
+/// A node or entry waiting in a nearest neighbour search
+#[derive(Debug)]
+struct Candidate {
+    distance: f64,
+    /// Level of the node, or `None` for an entry
+    level: Option<usize>,
+    index: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance.total_cmp(&other.distance)
+    }
+}
+
+/// StopIndex
+/// An R-tree over the stops of a feed for nearest stop, radius and bounding
+/// box queries. Points are (lat, lon) pairs and distances are in meters.
+#[derive(Debug)]
+pub struct StopIndex<'a> {
+    entries: Vec<Entry<'a>>,
+    /// From the leaves up to the root level
+    levels: Vec<Vec<Node>>,
+}
+
+impl<'a> StopIndex<'a> {
+    /// Packs the stops of `feed` with Sort-Tile-Recursive
+    pub fn new(feed: &'a Feed) -> Self {
+        let stations: HashMap<&str, &Stop> = feed
+            .stops
+            .iter()
+            .filter(|s| s.location_type == LocationType::Station)
+            .map(|s| (s.stop_id.as_str(), s))
+            .collect();
+        let mut entries: Vec<Entry> = feed
+            .stops
+            .iter()
+            .map(|stop| {
+                let parent = stop.parent_station.as_deref().and_then(|p| stations.get(p));
+                let wheelchair_boarding = match (&stop.wheelchair_boarding, parent) {
+                    (WheelchairAccessible::NoInformation, Some(parent)) => {
+                        &parent.wheelchair_boarding
+                    }
+                    (wheelchair_boarding, _) => wheelchair_boarding,
+                };
+                Entry {
+                    stop,
+                    position: (stop.stop_lat, stop.stop_lon),
+                    wheelchair_boarding,
+                }
+            })
+            .collect();
+
+        let leaves = entries.len().div_ceil(NODE_SIZE);
+        let slices = (leaves as f64).sqrt().ceil() as usize;
+        let slice_size = (slices * NODE_SIZE).max(1);
+        entries.sort_by(|a, b| a.position.1.total_cmp(&b.position.1));
+        for slice in entries.chunks_mut(slice_size) {
+            slice.sort_by(|a, b| a.position.0.total_cmp(&b.position.0));
+        }
+        let boxes: Vec<BBox> = entries.iter().map(|e| BBox::point(e.position)).collect();
+
+        let mut levels = Vec::new();
+        let mut level = pack(&boxes);
+        while level.len() > 1 {
+            let boxes: Vec<BBox> = level.iter().map(|n| n.bbox).collect();
+            levels.push(level);
+            level = pack(&boxes);
+        }
+        levels.push(level);
+        StopIndex { entries, levels }
+    }
+
+    /// The `k` stops closest to `point` matching `filter`, nearest first,
+    /// with their distance
+    pub fn nearest(
+        &self,
+        point: (f64, f64),
+        k: usize,
+        filter: &StopFilter,
+    ) -> Vec<(&'a Stop, f64)> {
+        let mut found = Vec::new();
+        let mut queue = BinaryHeap::new();
+        let root = self.levels.len() - 1;
+        for index in 0..self.levels[root].len() {
+            let distance = self.levels[root][index].bbox.min_distance(point);
+            queue.push(Reverse(Candidate {
+                distance,
+                level: Some(root),
+                index,
+            }));
+        }
+        while let Some(Reverse(candidate)) = queue.pop() {
+            if found.len() >= k {
+                break;
+            }
+            let level = match candidate.level {
+                Some(level) => level,
+                None => {
+                    found.push((self.entries[candidate.index].stop, candidate.distance));
+                    continue;
+                }
+            };
+            for index in self.levels[level][candidate.index].children.clone() {
+                let candidate = if level == 0 {
+                    let entry = &self.entries[index];
+                    if !filter.matches(entry) {
+                        continue;
+                    }
+                    Candidate {
+                        distance: haversine(point, entry.position),
+                        level: None,
+                        index,
+                    }
+                } else {
+                    Candidate {
+                        distance: self.levels[level - 1][index].bbox.min_distance(point),
+                        level: Some(level - 1),
+                        index,
+                    }
+                };
+                queue.push(Reverse(candidate));
+            }
+        }
+        found
+    }
+
+    /// Stops matching `filter` within `radius` meters of `point`, nearest
+    /// first, with their distance
+    pub fn within_radius(
+        &self,
+        point: (f64, f64),
+        radius: f64,
+        filter: &StopFilter,
+    ) -> Vec<(&'a Stop, f64)> {
+        let mut found = Vec::new();
+        self.search(
+            |bbox| bbox.min_distance(point) <= radius,
+            |entry| {
+                let distance = haversine(point, entry.position);
+                if distance <= radius && filter.matches(entry) {
+                    found.push((entry.stop, distance));
+                }
+            },
+        );
+        found.sort_by(|a, b| a.1.total_cmp(&b.1));
+        found
+    }
+
+    /// Stops matching `filter` inside the box from the (lat, lon) corner
+    /// `min` to `max`, in no particular order
+    pub fn in_bbox(&self, min: (f64, f64), max: (f64, f64), filter: &StopFilter) -> Vec<&'a Stop> {
+        let query = BBox { min, max };
+        let mut found = Vec::new();
+        self.search(
+            |bbox| bbox.intersects(&query),
+            |entry| {
+                if query.contains(entry.position) && filter.matches(entry) {
+                    found.push(entry.stop);
+                }
+            },
+        );
+        found
+    }
+
+    /// Calls `visit` with the entries of all leaves reached by descending
+    /// into the nodes passing `descend`
+    fn search(&self, descend: impl Fn(&BBox) -> bool, mut visit: impl FnMut(&Entry<'a>)) {
+        let root = self.levels.len() - 1;
+        let mut stack: Vec<(usize, usize)> = (0..self.levels[root].len())
+            .map(|index| (root, index))
+            .collect();
+        while let Some((level, index)) = stack.pop() {
+            let node = &self.levels[level][index];
+            if !descend(&node.bbox) {
+                continue;
+            }
+            if level == 0 {
+                node.children
+                    .clone()
+                    .for_each(|index| visit(&self.entries[index]));
+            } else {
+                stack.extend(node.children.clone().map(|index| (level - 1, index)));
+            }
+        }
+    }
+}
+
+/// Groups consecutive boxes into nodes
+fn pack(boxes: &[BBox]) -> Vec<Node> {
+    (0..boxes.len())
+        .step_by(NODE_SIZE)
+        .map(|start| {
+            let children = start..(start + NODE_SIZE).min(boxes.len());
+            let bbox = boxes[children.clone()]
+                .iter()
+                .fold(boxes[start], |b, c| b.union(c));
+            Node { bbox, children }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_min_distance() {
+        let bbox = BBox {
+            min: (36.4, -117.2),
+            max: (36.9, -116.4),
+        };
+        let points = [
+            (36.5, -116.8),
+            (37.5, -116.8),
+            (36.6, -118.0),
+            (80.0, -100.0),
+            (-36.6, 63.0),
+        ];
+        for &p in &points {
+            let bound = bbox.min_distance(p);
+            for i in 0..=10 {
+                for j in 0..=10 {
+                    let lat = 36.4 + 0.05 * f64::from(i);
+                    let lon = -117.2 + 0.08 * f64::from(j);
+                    assert!(bound <= haversine(p, (lat, lon)) + 1e-6);
+                }
+            }
+        }
+        assert_eq!(0.0, bbox.min_distance(points[0]));
+        let north = bbox.min_distance(points[1]);
+        assert!((north - haversine(points[1], (36.9, -116.8))).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_queries_match_brute_force() {
+        let mut feed = Feed::default();
+        for i in 0..1000 {
+            let lat = 36.0 + f64::from(i % 37) * 0.013 + f64::from(i % 7) * 0.001;
+            let lon = -117.0 + f64::from(i / 37) * 0.017 - f64::from(i % 11) * 0.002;
+            feed.stops.push(Stop {
+                stop_id: i.to_string(),
+                stop_code: None,
+                stop_name: i.to_string(),
+                stop_desc: None,
+                stop_lat: lat,
+                stop_lon: lon,
+                zone_id: None,
+                stop_url: None,
+                location_type: LocationType::Stop,
+                parent_station: None,
+                stop_timezone: None,
+                wheelchair_boarding: WheelchairAccessible::NoInformation,
+            });
+        }
+        let index = StopIndex::new(&feed);
+        assert!(index.levels.len() > 2);
+        let filter = StopFilter::default();
+        let point = (36.2, -116.8);
+        let mut all: Vec<f64> = feed
+            .stops
+            .iter()
+            .map(|s| haversine(point, (s.stop_lat, s.stop_lon)))
+            .collect();
+        all.sort_by(f64::total_cmp);
+
+        let nearest: Vec<f64> = index
+            .nearest(point, 25, &filter)
+            .iter()
+            .map(|n| n.1)
+            .collect();
+        assert_eq!(all[..25], nearest[..]);
+        let within = index.within_radius(point, 3000.0, &filter);
+        assert_eq!(all.iter().filter(|d| **d <= 3000.0).count(), within.len());
+        let (min, max) = ((36.1, -116.9), (36.3, -116.7));
+        let inside = feed
+            .stops
+            .iter()
+            .filter(|s| BBox { min, max }.contains((s.stop_lat, s.stop_lon)))
+            .count();
+        assert_eq!(inside, index.in_bbox(min, max, &filter).len());
+    }
+}
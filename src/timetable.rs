@@ -0,0 +1,227 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+use crate::feed::Feed;
+use crate::{
+    Calendar, ExceptionType, LocationType, Route, StopServiceType, StopTime, TimeOffset, Trip,
+};
+
+/// ServiceCalendar
+/// The dates each service runs on, from calendar.txt and calendar_dates.txt
+#[derive(Debug)]
+pub struct ServiceCalendar<'a> {
+    calendars: HashMap<&'a str, &'a Calendar>,
+    exceptions: HashMap<(&'a str, NaiveDate), &'a ExceptionType>,
+}
+
+impl<'a> ServiceCalendar<'a> {
+    pub fn new(feed: &'a Feed) -> Self {
+        ServiceCalendar {
+            calendars: feed
+                .calendars
+                .iter()
+                .map(|c| (c.service_id.as_str(), c))
+                .collect(),
+            exceptions: feed
+                .calendar_dates
+                .iter()
+                .map(|d| ((d.service_id.as_str(), d.date), &d.exception_type))
+                .collect(),
+        }
+    }
+
+    /// Whether `service_id` runs on `date`. Exceptions in calendar_dates.txt
+    /// override the weekly pattern of calendar.txt.
+    pub fn is_active(&self, service_id: &str, date: NaiveDate) -> bool {
+        match self.exceptions.get(&(service_id, date)) {
+            Some(ExceptionType::ServiceAdded) => return true,
+            Some(ExceptionType::ServiceRemoved) => return false,
+            None => (),
+        }
+        let calendar = match self.calendars.get(service_id) {
+            Some(calendar) => calendar,
+            None => return false,
+        };
+        if date < calendar.start_date || date > calendar.end_date {
+            return false;
+        }
+        match date.weekday() {
+            Weekday::Mon => calendar.monday,
+            Weekday::Tue => calendar.tuesday,
+            Weekday::Wed => calendar.wednesday,
+            Weekday::Thu => calendar.thursday,
+            Weekday::Fri => calendar.friday,
+            Weekday::Sat => calendar.saturday,
+            Weekday::Sun => calendar.sunday,
+        }
+    }
+
+    /// The services running on `date`
+    pub fn active_services(&self, date: NaiveDate) -> HashSet<&'a str> {
+        let weekly = self.calendars.keys().copied();
+        let added = self.exceptions.keys().map(|(service_id, _)| *service_id);
+        weekly
+            .chain(added)
+            .filter(|service_id| self.is_active(service_id, date))
+            .collect()
+    }
+}
+
+/// A trip leaving a stop, see `Timetable::departures`
+#[derive(Debug)]
+pub struct Departure<'a> {
+    pub trip: &'a Trip,
+    pub route: &'a Route,
+    pub stop_time: &'a StopTime,
+    /// stop_headsign, or trip_headsign when the stop time has none
+    pub headsign: Option<&'a str>,
+    /// The service day the trip belongs to, which is the day before the
+    /// queried date for trips running past midnight
+    pub service_date: NaiveDate,
+    /// The scheduled departure, measured from the queried date
+    pub departure_time: TimeOffset,
+}
+
+/// Timetable
+/// Stop times of a feed indexed by stop, for timetable queries
+#[derive(Debug)]
+pub struct Timetable<'a> {
+    calendar: ServiceCalendar<'a>,
+    trips: HashMap<&'a str, &'a Trip>,
+    routes: HashMap<&'a str, &'a Route>,
+    children: HashMap<&'a str, Vec<&'a str>>,
+    departures: HashMap<&'a str, Vec<&'a StopTime>>,
+}
+
+impl<'a> Timetable<'a> {
+    pub fn new(feed: &'a Feed) -> Self {
+        let stations: HashSet<&str> = feed
+            .stops
+            .iter()
+            .filter(|s| s.location_type == LocationType::Station)
+            .map(|s| s.stop_id.as_str())
+            .collect();
+        let mut children: HashMap<&str, Vec<&str>> = HashMap::new();
+        for stop in &feed.stops {
+            match stop.parent_station.as_deref() {
+                Some(parent) if stations.contains(parent) => {
+                    children.entry(parent).or_default().push(&stop.stop_id)
+                }
+                _ => (),
+            }
+        }
+
+        // The last stop of a trip is where it arrives, not departs
+        let mut last_sequence: HashMap<&str, u64> = HashMap::new();
+        for stop_time in &feed.stop_times {
+            let last = last_sequence.entry(&stop_time.trip_id).or_default();
+            *last = (*last).max(stop_time.stop_sequence);
+        }
+        let mut departures: HashMap<&str, Vec<&StopTime>> = HashMap::new();
+        for stop_time in &feed.stop_times {
+            if stop_time.pickup_type == StopServiceType::NoServiceAvailable
+                || last_sequence[stop_time.trip_id.as_str()] == stop_time.stop_sequence
+            {
+                continue;
+            }
+            departures
+                .entry(&stop_time.stop_id)
+                .or_default()
+                .push(stop_time);
+        }
+        for stop_times in departures.values_mut() {
+            stop_times.sort_by(|a, b| a.departure_time.cmp(&b.departure_time));
+        }
+
+        Timetable {
+            calendar: ServiceCalendar::new(feed),
+            trips: feed.trips.iter().map(|t| (t.trip_id.as_str(), t)).collect(),
+            routes: feed
+                .routes
+                .iter()
+                .map(|r| (r.route_id.as_str(), r))
+                .collect(),
+            children,
+            departures,
+        }
+    }
+
+    pub fn calendar(&self) -> &ServiceCalendar<'a> {
+        &self.calendar
+    }
+
+    /// Trips leaving `stop_id` on `date` within `window`, ordered by
+    /// departure time. Departures of a station include those of its child
+    /// stops, trips of the previous service day running past midnight are
+    /// included, as are those of the next service day when the window runs
+    /// past 24:00, and stop times without pickup are not.
+    pub fn departures(
+        &self,
+        stop_id: &str,
+        date: NaiveDate,
+        window: Range<TimeOffset>,
+    ) -> Vec<Departure<'a>> {
+        let mut stop_ids = vec![stop_id];
+        if let Some(children) = self.children.get(stop_id) {
+            stop_ids.extend(children);
+        }
+        let from = window.start.duration();
+        let to = window.end.duration();
+        let next = if to >= Duration::days(1) {
+            date.succ_opt()
+        } else {
+            None
+        };
+        let mut departures = Vec::new();
+        for service_date in [date.pred_opt(), Some(date), next].iter().flatten() {
+            let offset = date.signed_duration_since(*service_date);
+            for stop_id in &stop_ids {
+                let stop_times = match self.departures.get(stop_id) {
+                    Some(stop_times) => stop_times,
+                    None => continue,
+                };
+                let time = |st: &&StopTime| st.departure_time.duration() - offset;
+                let start = stop_times.partition_point(|st| time(st) < from);
+                for stop_time in &stop_times[start..] {
+                    if time(stop_time) >= to {
+                        break;
+                    }
+                    if let Some(departure) =
+                        self.departure(stop_time, *service_date, time(stop_time))
+                    {
+                        departures.push(departure);
+                    }
+                }
+            }
+        }
+        departures.sort_by(|a, b| a.departure_time.cmp(&b.departure_time));
+        departures
+    }
+
+    fn departure(
+        &self,
+        stop_time: &'a StopTime,
+        service_date: NaiveDate,
+        time: Duration,
+    ) -> Option<Departure<'a>> {
+        let trip = self.trips.get(stop_time.trip_id.as_str())?;
+        if !self.calendar.is_active(&trip.service_id, service_date) {
+            return None;
+        }
+        let route = self.routes.get(trip.route_id.as_str())?;
+        let seconds = time.num_seconds() as u32;
+        Some(Departure {
+            trip,
+            route,
+            stop_time,
+            headsign: stop_time
+                .stop_headsign
+                .as_deref()
+                .or(trip.trip_headsign.as_deref()),
+            service_date,
+            departure_time: TimeOffset::from_hms(seconds / 3600, seconds / 60 % 60, seconds % 60),
+        })
+    }
+}
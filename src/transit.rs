@@ -1,9 +1,9 @@
 use super::gtfs::parse::*;
 use chrono::{Duration, NaiveDate};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// Agency
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, PartialEq)]
 pub struct Agency {
     pub agency_id: Option<String>,
     pub agency_name: String,
@@ -67,7 +67,7 @@ pub struct Stop {
 }
 
 /// RouteType
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum RouteType {
     LightRail,
     Subway,
@@ -102,7 +102,7 @@ impl<'de> serde::Deserialize<'de> for RouteType {
 }
 
 /// Route
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, PartialEq)]
 pub struct Route {
     pub route_id: String,
     pub agency_id: Option<String>,
@@ -151,7 +151,7 @@ impl<'de> serde::Deserialize<'de> for WheelchairAccessible {
 }
 
 /// Bikes Allowed
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum BikesAllowed {
     NoInformation,
     SomeBikes,
@@ -185,7 +185,7 @@ impl<'de> serde::Deserialize<'de> for BikesAllowed {
 }
 
 /// Trip
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, PartialEq)]
 pub struct Trip {
     pub route_id: String,
     pub service_id: String,
@@ -221,19 +221,16 @@ impl<'de> serde::Deserialize<'de> for StopServiceType {
     where
         D: serde::Deserializer<'de>,
     {
-        let result: String = serde::Deserialize::deserialize(deserializer)?;
-        match result.trim() {
+        deserialize_str_with(deserializer, |result| match result.trim() {
             "" => Ok(StopServiceType::RegularlyScheduled),
             r => match r.parse::<u32>() {
                 Ok(0) => Ok(StopServiceType::RegularlyScheduled),
                 Ok(1) => Ok(StopServiceType::NoServiceAvailable),
                 Ok(2) => Ok(StopServiceType::MustPhoneAgency),
                 Ok(3) => Ok(StopServiceType::MustCoordinateWithDriver),
-                _ => Err(serde::de::Error::custom(
-                    "StopService type must be between 0 and 3",
-                )),
+                _ => Err("StopService type must be between 0 and 3".to_string()),
             },
-        }
+        })
     }
 }
 
@@ -255,15 +252,14 @@ impl<'de> serde::Deserialize<'de> for Timepoint {
     where
         D: serde::Deserializer<'de>,
     {
-        let result: String = serde::Deserialize::deserialize(deserializer)?;
-        match result.trim() {
+        deserialize_str_with(deserializer, |result| match result.trim() {
             "" => Ok(Timepoint::Exact),
             r => match r.parse::<u32>() {
                 Ok(0) => Ok(Timepoint::Approximate),
                 Ok(1) => Ok(Timepoint::Exact),
-                _ => Err(serde::de::Error::custom("Timepoint must be 0 or 1")),
+                _ => Err("Timepoint must be 0 or 1".to_string()),
             },
-        }
+        })
     }
 }
 
@@ -285,8 +281,45 @@ pub struct StopTime {
     pub timepoint: Timepoint,
 }
 
+/// StopTimeRef
+/// A `StopTime` borrowing its text fields from the record it was read from,
+/// see `GTFSRecordReader`
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct StopTimeRef<'a> {
+    pub trip_id: &'a str,
+    pub arrival_time: TimeOffset,
+    pub departure_time: TimeOffset,
+    pub stop_id: &'a str,
+    pub stop_sequence: u64,
+    pub stop_headsign: Option<&'a str>,
+    #[serde(default)]
+    pub pickup_type: StopServiceType,
+    #[serde(default)]
+    pub dropoff_type: StopServiceType,
+    pub shape_dist_traveled: Option<f64>,
+    #[serde(default)]
+    pub timepoint: Timepoint,
+}
+
+impl<'a> From<StopTimeRef<'a>> for StopTime {
+    fn from(stop_time: StopTimeRef<'a>) -> StopTime {
+        StopTime {
+            trip_id: stop_time.trip_id.to_string(),
+            arrival_time: stop_time.arrival_time,
+            departure_time: stop_time.departure_time,
+            stop_id: stop_time.stop_id.to_string(),
+            stop_sequence: stop_time.stop_sequence,
+            stop_headsign: stop_time.stop_headsign.map(|s| s.to_string()),
+            pickup_type: stop_time.pickup_type,
+            dropoff_type: stop_time.dropoff_type,
+            shape_dist_traveled: stop_time.shape_dist_traveled,
+            timepoint: stop_time.timepoint,
+        }
+    }
+}
+
 /// Calendar
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, PartialEq)]
 pub struct Calendar {
     pub service_id: String,
     #[serde(deserialize_with = "deserialize_dow_field")]
@@ -310,7 +343,7 @@ pub struct Calendar {
 }
 
 /// ExceptionType for `CalendarDate`
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum ExceptionType {
     ServiceAdded,
     ServiceRemoved,
@@ -333,7 +366,7 @@ impl<'de> serde::Deserialize<'de> for ExceptionType {
 }
 
 /// CalendarDate
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, PartialEq)]
 pub struct CalendarDate {
     pub service_id: String,
     #[serde(deserialize_with = "deserialize_calendardate")]
@@ -342,7 +375,7 @@ pub struct CalendarDate {
 }
 
 /// PaymentMethod for `FareAttribute`
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum PaymentMethod {
     PaidOnboard,
     PaidBefore,
@@ -363,7 +396,7 @@ impl<'de> serde::Deserialize<'de> for PaymentMethod {
 }
 
 /// Tranfers for `FareAttribute`
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum Transfers {
     None,
     TransferOnce,
@@ -392,7 +425,7 @@ impl<'de> serde::Deserialize<'de> for Transfers {
 }
 
 /// FareAttribute
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, PartialEq)]
 pub struct FareAttribute {
     pub fare_id: String,
     pub price: f64,
@@ -405,7 +438,7 @@ pub struct FareAttribute {
 
 /// FareRule
 /// origin, destination, and contains reference a zone_id from stops
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, PartialEq, Eq, Hash)]
 pub struct FareRule {
     pub fare_id: String,
     pub route_id: Option<String>,
@@ -416,7 +449,7 @@ pub struct FareRule {
 
 /// ShapePoint
 /// A row from shapes.txt
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ShapePoint {
     pub shape_id: String,
     pub shape_pt_lat: f64,
@@ -448,31 +481,45 @@ impl TimeOffset {
     }
 }
 
+impl std::fmt::Display for TimeOffset {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}:{:02}",
+            self.hours, self.minutes, self.seconds
+        )
+    }
+}
+
+impl std::str::FromStr for TimeOffset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split(':');
+        let mut parse_part = || -> Result<u32, String> {
+            match parts.next() {
+                Some(val) => val.parse().map_err(|e| format!("{}", e)),
+                None => Err("Unexpected timeoffset part".to_string()),
+            }
+        };
+        let hours = parse_part()?;
+        let minutes = parse_part()?;
+        let seconds = parse_part()?;
+        Ok(TimeOffset::from_hms(hours, minutes, seconds))
+    }
+}
+
 impl<'de> serde::Deserialize<'de> for TimeOffset {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let result: String = serde::Deserialize::deserialize(deserializer)?;
-        let mut parts = result.trim().split(':');
-        let parse_part = |part: Option<&str>| -> Result<u32, D::Error> {
-            match part {
-                Some(val) => match val.parse() {
-                    Ok(x) => Ok(x),
-                    Err(y) => Err(serde::de::Error::custom(y)),
-                },
-                None => Err(serde::de::Error::custom("Unexpected timeoffset part")),
-            }
-        };
-        let hours = parse_part(parts.next())?;
-        let minutes = parse_part(parts.next())?;
-        let seconds = parse_part(parts.next())?;
-        Ok(TimeOffset::from_hms(hours, minutes, seconds))
+        deserialize_str_with(deserializer, str::parse)
     }
 }
 
 /// exact_times for Frequency
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum FrequencyAccuracy {
     Approximate,
     Exact,
@@ -504,7 +551,7 @@ impl<'de> serde::Deserialize<'de> for FrequencyAccuracy {
 }
 
 /// Frequency
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, PartialEq)]
 pub struct Frequency {
     pub trip_id: String,
     pub start_time: TimeOffset,
@@ -514,7 +561,7 @@ pub struct Frequency {
     pub exact_times: FrequencyAccuracy,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub enum TransferType {
     Recommended,
     Timed,
@@ -541,7 +588,7 @@ impl<'de> serde::Deserialize<'de> for TransferType {
 }
 
 /// Transfer
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, PartialEq, Eq, Hash)]
 pub struct Transfer {
     pub from_stop_id: String,
     pub to_stop_id: String,
@@ -551,7 +598,7 @@ pub struct Transfer {
 }
 
 /// Feed Info
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, PartialEq)]
 pub struct FeedInfo {
     pub feed_publisher_name: String,
     pub feed_publisher_url: String,
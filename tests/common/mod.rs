@@ -0,0 +1,5 @@
+use transitfeed::{Feed, FeedReader};
+
+pub fn good_feed() -> Feed {
+    FeedReader::new("./examples/good_feed").load().unwrap()
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use chrono::NaiveDate;
+use transitfeed::{ChangeKind, FieldChange, ScheduleChange, TimeOffset};
+
+use common::good_feed;
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+fn fields_of(fields: &[FieldChange]) -> Vec<&str> {
+    fields.iter().map(|f| f.field).collect()
+}
+
+#[test]
+fn test_diff_same_feed() {
+    let (before, after) = (good_feed(), good_feed());
+    let diff = before.diff(&after);
+    assert!(diff.is_empty());
+    assert!(diff
+        .schedule_changes(date(2024, 6, 8)..=date(2024, 6, 10))
+        .is_empty());
+}
+
+#[test]
+fn test_diff_records() {
+    let before = good_feed();
+    let mut after = good_feed();
+    for stop in after.stops.iter_mut() {
+        match stop.stop_id.as_str() {
+            "FUR_CREEK_RES" => stop.stop_name = "Furnace Creek".to_string(),
+            "EMSI" => stop.stop_id = "EMSI2".to_string(),
+            _ => {}
+        }
+    }
+    after.routes.retain(|r| r.route_id != "AAMV");
+    let stop_time = after
+        .stop_times
+        .iter_mut()
+        .find(|st| st.trip_id == "CITY1" && st.stop_sequence == 0)
+        .unwrap();
+    stop_time.departure_time = TimeOffset::from_hms(6, 1, 0);
+    after.calendars[1].end_date = date(2024, 6, 7);
+
+    let diff = before.diff(&after);
+    let stops: Vec<(&str, ChangeKind)> = diff.stops.iter().map(|c| (c.id, c.kind())).collect();
+    assert_eq!(
+        vec![
+            ("EMSI", ChangeKind::Removed),
+            ("EMSI2", ChangeKind::Added),
+            ("FUR_CREEK_RES", ChangeKind::Modified),
+        ],
+        stops
+    );
+    assert!(diff.stops[0].fields.is_empty());
+    let fields = &diff.stops[2].fields;
+    assert_eq!(1, fields.len());
+    assert_eq!("stop_name", fields[0].field);
+    assert_eq!("\"Furnace Creek\"", fields[0].after);
+
+    assert_eq!(1, diff.routes.len());
+    assert_eq!(ChangeKind::Removed, diff.routes[0].kind());
+    assert_eq!(
+        Some("AAMV"),
+        diff.routes[0].before.map(|r| r.route_id.as_str())
+    );
+
+    assert_eq!(1, diff.trips.len());
+    assert_eq!("CITY1", diff.trips[0].id);
+    assert_eq!(vec!["stop_times"], fields_of(&diff.trips[0].fields));
+
+    assert_eq!(1, diff.services.len());
+    assert_eq!("WE", diff.services[0].id);
+    assert_eq!(vec!["end_date"], fields_of(&diff.services[0].fields));
+}
+
+#[test]
+fn test_diff_schedule_changes() {
+    let before = good_feed();
+    let mut after = good_feed();
+    // No weekend service from Saturday 8 June 2024 on
+    after.calendars[1].end_date = date(2024, 6, 7);
+    after.trips.retain(|t| t.trip_id != "BFC2");
+    after.stop_times.retain(|st| st.trip_id != "BFC2");
+    after.frequencies[0].headway_secs = 3600;
+
+    let diff = before.diff(&after);
+    let changes = diff.schedule_changes(date(2024, 6, 8)..=date(2024, 6, 10));
+    let change = |route_id, day, before, after| ScheduleChange {
+        route_id,
+        date: date(2024, 6, day),
+        before,
+        after,
+    };
+    assert_eq!(
+        vec![
+            change("AAMV", 8, 4, 0),
+            change("AAMV", 9, 4, 0),
+            change("BFC", 8, 2, 1),
+            change("BFC", 9, 2, 1),
+            change("BFC", 10, 2, 1),
+            change("STBA", 8, 32, 16),
+            change("STBA", 9, 32, 16),
+            change("STBA", 10, 32, 16),
+        ],
+        changes
+    );
+}
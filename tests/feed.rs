@@ -1,4 +1,5 @@
-use transitfeed::{FeedReader, Trim};
+use std::fs;
+use transitfeed::{FeedReader, InternedFeed, Requirement, Trim};
 
 #[test]
 fn test_read_feed_with_reader_options() {
@@ -29,3 +30,185 @@ fn test_fail_bad_zipfiles() {
         "Error didn't match"
     );
 }
+
+#[test]
+fn test_manifest_lists_present_and_missing_files() {
+    let feed = FeedReader::new("./examples/good_feed");
+    let manifest = feed.manifest().unwrap();
+
+    let agency = manifest.get("agency.txt").unwrap();
+    assert!(agency.present);
+    assert_eq!(Requirement::Required, agency.requirement);
+    assert_eq!(Some(1), agency.row_count);
+    assert_eq!(
+        Some(vec![
+            "agency_id".to_string(),
+            "agency_name".to_string(),
+            "agency_url".to_string(),
+            "agency_timezone".to_string(),
+            "agency_phone".to_string(),
+        ]),
+        agency.headers
+    );
+    assert_eq!(Some(28), manifest.get("stop_times.txt").unwrap().row_count);
+
+    let pathways = manifest.get("pathways.txt").unwrap();
+    assert!(!pathways.present);
+    assert_eq!(None, pathways.row_count);
+    assert_eq!(None, pathways.headers);
+
+    assert!(manifest.is_present("calendar.txt"));
+    assert_eq!(0, manifest.missing_required().count());
+    assert!(manifest.unrecognized.is_empty());
+}
+
+#[test]
+fn test_manifest_reports_missing_required_and_unrecognized_files() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("stops.txt"), "stop_id,stop_name\n").unwrap();
+    fs::write(dir.path().join("notes.md"), "not gtfs").unwrap();
+
+    let feed = FeedReader::new(dir.path().to_str().unwrap());
+    let manifest = feed.manifest().unwrap();
+
+    assert_eq!(Some(0), manifest.get("stops.txt").unwrap().row_count);
+    let missing: Vec<_> = manifest
+        .missing_required()
+        .map(|e| e.filename.as_str())
+        .collect();
+    assert_eq!(
+        vec!["agency.txt", "routes.txt", "trips.txt", "stop_times.txt"],
+        missing
+    );
+    assert_eq!(vec!["notes.md".to_string()], manifest.unrecognized);
+}
+
+#[test]
+fn test_manifest_records_unreadable_files() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("stops.txt"), "stop_id,stop_name\nA\n").unwrap();
+    fs::write(
+        dir.path().join("trips.txt"),
+        "route_id,service_id,trip_id\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("locations.geojson"), "{}").unwrap();
+
+    let feed = FeedReader::new(dir.path().to_str().unwrap());
+    let manifest = feed.manifest().unwrap();
+
+    let stops = manifest.get("stops.txt").unwrap();
+    assert!(stops.present);
+    assert_eq!(None, stops.row_count);
+    assert!(stops.error.is_some());
+    let trips = manifest.get("trips.txt").unwrap();
+    assert_eq!(Some(0), trips.row_count);
+    assert_eq!(None, trips.error);
+    let locations = manifest.get("locations.geojson").unwrap();
+    assert!(locations.present);
+    assert_eq!(None, locations.error);
+    assert_eq!(
+        Some(Requirement::ConditionallyForbidden),
+        manifest.get("networks.txt").map(|e| e.requirement)
+    );
+}
+
+#[test]
+fn test_load_interned_feed() {
+    let feed = FeedReader::new("./examples/good_feed");
+    let interned = InternedFeed::load(&feed).unwrap();
+
+    assert_eq!(10, interned.ids.stops.len());
+    assert_eq!(11, interned.trips.len());
+    assert_eq!(28, interned.stop_times.len());
+
+    let first = &interned.stop_times[0];
+    assert_eq!("STBA", interned.ids.trips.resolve(first.trip));
+    assert_eq!("STAGECOACH", interned.ids.stops.resolve(first.stop));
+    assert_eq!(Some(first.stop), interned.ids.stops.get("STAGECOACH"));
+
+    let trip = &interned.trips[0];
+    assert_eq!("AB", interned.ids.routes.resolve(trip.route));
+    assert_eq!("FULLW", interned.ids.services.resolve(trip.service));
+}
+
+#[test]
+fn test_load_feed() {
+    let feed = FeedReader::new("./examples/good_feed").load().unwrap();
+    assert_eq!(10, feed.stops.len());
+    assert_eq!(28, feed.stop_times.len());
+    assert_eq!(12, feed.shapes.len());
+}
+
+#[test]
+fn test_load_feed_without_optional_files() {
+    let feed = FeedReader::from_zip("./examples/good_feed.zip")
+        .unwrap()
+        .load()
+        .unwrap();
+    assert_eq!(11, feed.trips.len());
+    assert!(feed.shapes.is_empty());
+    assert!(feed.feed_info.is_empty());
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par_load_feed() {
+    let reader = FeedReader::new("./examples/good_feed");
+    let feed = reader.par_load().unwrap();
+    assert_eq!(reader.load().unwrap().stop_times, feed.stop_times);
+    assert_eq!(10, feed.stops.len());
+    assert_eq!(12, feed.shapes.len());
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par_load_custom_terminator() {
+    let dir = tempfile::tempdir().unwrap();
+    for entry in fs::read_dir("./examples/good_feed").unwrap() {
+        let path = entry.unwrap().path();
+        let contents = fs::read_to_string(&path).unwrap();
+        let contents = contents.replace("\r\n", ";").replace('\n', ";");
+        fs::write(dir.path().join(path.file_name().unwrap()), contents).unwrap();
+    }
+    let mut reader = FeedReader::new(dir.path().to_str().unwrap());
+    reader
+        .builder()
+        .terminator(transitfeed::Terminator::Any(b';'));
+    let feed = reader.par_load().unwrap();
+    let expected = reader.load().unwrap();
+    assert_eq!(28, feed.stop_times.len());
+    assert_eq!(expected.stop_times, feed.stop_times);
+    assert_eq!(expected.shapes, feed.shapes);
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn test_read_mmap_feed() {
+    let feed = FeedReader::from_mmap("./examples/good_feed");
+    let mut records = feed.stop_time_records().unwrap();
+    let mut count = 0;
+    while let Some(result) = records.read::<transitfeed::StopTimeRef>() {
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        count += 1;
+    }
+    assert_eq!(28, count);
+    assert_eq!(
+        FeedReader::new("./examples/good_feed")
+            .load()
+            .unwrap()
+            .stops,
+        feed.load().unwrap().stops
+    );
+}
+
+#[cfg(all(feature = "mmap", feature = "rayon"))]
+#[test]
+fn test_par_load_mmap_feed() {
+    let feed = FeedReader::from_mmap("./examples/good_feed")
+        .par_load()
+        .unwrap();
+    let expected = FeedReader::new("./examples/good_feed").load().unwrap();
+    assert_eq!(expected.stop_times, feed.stop_times);
+    assert_eq!(expected.shapes, feed.shapes);
+}
@@ -0,0 +1,162 @@
+mod common;
+
+use chrono::NaiveDate;
+use transitfeed::filter::{
+    filter_area, filter_dates, filter_routes, Area, RouteSelection, TripClipping,
+};
+use transitfeed::RouteType;
+
+use common::good_feed;
+
+fn ids<T>(records: &[T], id: impl Fn(&T) -> &str) -> Vec<String> {
+    let mut ids: Vec<String> = records.iter().map(|r| id(r).to_string()).collect();
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+/// Around the stops of the CITY route in Beatty
+fn town() -> Area {
+    Area::BBox {
+        min: (36.9, -116.78),
+        max: (36.92, -116.75),
+    }
+}
+
+#[test]
+fn test_area_contains() {
+    assert!(town().contains((36.91, -116.76)));
+    assert!(!town().contains((36.88, -116.76)));
+    let triangle = Area::Polygon(vec![(0.0, 0.0), (0.0, 2.0), (2.0, 0.0)]);
+    assert!(triangle.contains((0.5, 0.5)));
+    assert!(!triangle.contains((1.5, 1.5)));
+    assert!(!triangle.contains((-0.5, 0.5)));
+}
+
+#[test]
+fn test_filter_area_whole_trips() {
+    let mut feed = good_feed();
+    filter_area(&mut feed, &town(), TripClipping::Whole);
+    assert_eq!(
+        vec!["CITY1", "CITY2", "STBA"],
+        ids(&feed.trips, |t| &t.trip_id)
+    );
+    assert_eq!(vec!["CITY", "STBA"], ids(&feed.routes, |r| &r.route_id));
+    // STBA runs out to the airport, whose station is kept as well
+    assert_eq!(
+        vec![
+            "BEATTY_AIRPORT",
+            "BEATTY_AIRPORT_STATION",
+            "DADAN",
+            "EMSI",
+            "NADAV",
+            "NANAA",
+            "STAGECOACH"
+        ],
+        ids(&feed.stops, |s| &s.stop_id)
+    );
+    assert_eq!(12, feed.stop_times.len());
+    assert_eq!(11, feed.frequencies.len());
+    assert_eq!(vec!["FULLW"], ids(&feed.calendars, |c| &c.service_id));
+    assert_eq!(1, feed.calendar_dates.len());
+    assert_eq!(2, feed.transfers.len());
+    assert_eq!(vec!["p"], ids(&feed.fare_rules, |r| &r.fare_id));
+    assert_eq!(vec!["p"], ids(&feed.fare_attributes, |f| &f.fare_id));
+    assert_eq!(1, feed.agencies.len());
+}
+
+#[test]
+fn test_filter_area_truncated_trips() {
+    let mut feed = good_feed();
+    filter_area(&mut feed, &town(), TripClipping::Truncate);
+    // STBA only stops once in town
+    assert_eq!(vec!["CITY1", "CITY2"], ids(&feed.trips, |t| &t.trip_id));
+    assert_eq!(5, feed.stops.len());
+    assert_eq!(10, feed.stop_times.len());
+    assert_eq!(10, feed.frequencies.len());
+    assert!(feed.fare_rules.is_empty());
+    assert!(feed.fare_attributes.is_empty());
+
+    let mut feed = good_feed();
+    let nowhere = Area::Polygon(vec![(0.0, 0.0), (0.0, 1.0), (1.0, 0.0)]);
+    filter_area(&mut feed, &nowhere, TripClipping::Truncate);
+    assert!(feed.trips.is_empty());
+    assert!(feed.stops.is_empty());
+    assert!(feed.agencies.is_empty());
+}
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+#[test]
+fn test_filter_dates() {
+    let mut feed = good_feed();
+    // Weekdays only, so the weekend service to Amargosa Valley goes
+    filter_dates(&mut feed, date(2024, 6, 3)..=date(2024, 6, 7));
+    assert_eq!(vec!["FULLW"], ids(&feed.calendars, |c| &c.service_id));
+    assert_eq!(date(2024, 6, 3), feed.calendars[0].start_date);
+    assert_eq!(date(2024, 6, 7), feed.calendars[0].end_date);
+    assert!(feed.calendar_dates.is_empty());
+    assert_eq!(7, feed.trips.len());
+    assert!(!feed.routes.iter().any(|r| r.route_id == "AAMV"));
+    assert!(!feed.stops.iter().any(|s| s.stop_id == "AMV"));
+    assert_eq!(vec!["p"], ids(&feed.fare_attributes, |f| &f.fare_id));
+    assert_eq!(Some(date(2024, 6, 3)), feed.feed_info[0].feed_start_date);
+    assert_eq!(Some(date(2024, 6, 7)), feed.feed_info[0].feed_end_date);
+
+    // Calendars running past the end of the window keep their own end
+    let mut feed = good_feed();
+    filter_dates(&mut feed, date(2025, 12, 27)..=date(2026, 1, 10));
+    assert_eq!(2, feed.calendars.len());
+    assert!(feed
+        .calendars
+        .iter()
+        .all(|c| c.end_date == date(2025, 12, 31)));
+    assert_eq!(11, feed.trips.len());
+
+    // The only weekday FULLW was removed, and there was no other service
+    let mut feed = good_feed();
+    filter_dates(&mut feed, date(2007, 6, 4)..=date(2007, 6, 4));
+    assert!(feed.trips.is_empty());
+    assert!(feed.calendars.is_empty());
+    assert!(feed.calendar_dates.is_empty());
+}
+
+#[test]
+fn test_filter_routes() {
+    let mut feed = good_feed();
+    filter_routes(&mut feed, &RouteSelection::default().route("AB"));
+    assert_eq!(vec!["AB1", "AB2"], ids(&feed.trips, |t| &t.trip_id));
+    assert_eq!(
+        vec!["BEATTY_AIRPORT", "BEATTY_AIRPORT_STATION", "BULLFROG"],
+        ids(&feed.stops, |s| &s.stop_id)
+    );
+    assert_eq!(1, feed.fare_rules.len());
+    assert_eq!(vec!["p"], ids(&feed.fare_attributes, |f| &f.fare_id));
+    assert!(feed.frequencies.is_empty());
+    assert!(feed.transfers.is_empty());
+
+    let mut feed = good_feed();
+    let selection = RouteSelection::default()
+        .route_type(RouteType::Rail)
+        .route("CITY")
+        .route("AAMV");
+    filter_routes(&mut feed, &selection);
+    assert_eq!(vec!["AAMV", "CITY"], ids(&feed.routes, |r| &r.route_id));
+    assert_eq!(2, feed.calendars.len());
+
+    let mut feed = good_feed();
+    filter_routes(&mut feed, &RouteSelection::default().agency("DTA"));
+    assert_eq!(5, feed.routes.len());
+    assert_eq!(11, feed.trips.len());
+
+    let mut feed = good_feed();
+    filter_routes(
+        &mut feed,
+        &RouteSelection::default().route_type(RouteType::Rail),
+    );
+    assert!(feed.routes.is_empty());
+    assert!(feed.stops.is_empty());
+    assert!(feed.shapes.is_empty());
+}
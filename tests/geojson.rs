@@ -0,0 +1,77 @@
+#![cfg(feature = "geojson")]
+mod common;
+
+use serde_json::Value;
+use transitfeed::GeoJsonExporter;
+
+use common::good_feed;
+
+fn features(geojson: &str) -> Vec<Value> {
+    let collection: Value = serde_json::from_str(geojson).unwrap();
+    assert_eq!("FeatureCollection", collection["type"]);
+    collection["features"].as_array().unwrap().clone()
+}
+
+#[test]
+fn test_stops_geojson() {
+    let feed = good_feed();
+    let stops = features(&GeoJsonExporter::new(&feed).stops());
+    assert_eq!(feed.stops.len(), stops.len());
+    let airport = &stops[1];
+    assert_eq!("Point", airport["geometry"]["type"]);
+    assert_eq!(-116.784582, airport["geometry"]["coordinates"][0]);
+    assert_eq!(36.868446, airport["geometry"]["coordinates"][1]);
+    assert_eq!(
+        "Nye County Airport (Demo)",
+        airport["properties"]["stop_name"]
+    );
+    assert_eq!(0, airport["properties"]["location_type"]);
+    assert_eq!(
+        "BEATTY_AIRPORT_STATION",
+        airport["properties"]["parent_station"]
+    );
+    assert_eq!(0, airport["properties"]["wheelchair_boarding"]);
+    assert_eq!(1, stops[2]["properties"]["location_type"]);
+}
+
+#[test]
+fn test_shapes_geojson() {
+    let feed = good_feed();
+    let shapes = features(&GeoJsonExporter::new(&feed).shapes());
+    assert_eq!(3, shapes.len());
+    assert_eq!("shape_1", shapes[0]["properties"]["shape_id"]);
+    assert_eq!("LineString", shapes[0]["geometry"]["type"]);
+    assert_eq!(
+        serde_json::json!([[1.0, 1.0], [4.0, 2.0], [9.0, 3.0], [16.0, 4.0]]),
+        shapes[0]["geometry"]["coordinates"]
+    );
+}
+
+#[test]
+fn test_routes_geojson() {
+    let mut feed = good_feed();
+    for trip in feed.trips.iter_mut() {
+        trip.shape_id = match trip.trip_id.as_str() {
+            "AB1" | "AB2" => Some("shape_1".to_string()),
+            "CITY1" => Some("shape_2".to_string()),
+            "CITY2" => Some("shape_3".to_string()),
+            _ => None,
+        };
+    }
+    feed.routes[3].route_color = Some("FF0000".to_string());
+    let routes = features(&GeoJsonExporter::new(&feed).routes());
+    assert_eq!(feed.routes.len(), routes.len());
+
+    let ab = &routes[0];
+    assert_eq!("AB", ab["properties"]["route_id"]);
+    assert_eq!("MultiLineString", ab["geometry"]["type"]);
+    assert_eq!(1, ab["geometry"]["coordinates"].as_array().unwrap().len());
+
+    let city = &routes[3];
+    assert_eq!("Ō", city["properties"]["route_short_name"]);
+    assert_eq!("FF0000", city["properties"]["route_color"]);
+    assert_eq!(3, city["properties"]["route_type"]);
+    assert_eq!(2, city["geometry"]["coordinates"].as_array().unwrap().len());
+
+    assert!(routes[1]["geometry"].is_null());
+}
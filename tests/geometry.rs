@@ -0,0 +1,44 @@
+#![cfg(feature = "geo")]
+use transitfeed::geo_types::{coord, LineString, Point};
+use transitfeed::FeedReader;
+
+#[test]
+fn test_stop_point() {
+    let feed = FeedReader::new("./examples/good_feed").load().unwrap();
+    let stop = feed.stops.iter().find(|s| s.stop_id == "BULLFROG").unwrap();
+    assert_eq!(Point::new(-116.81797, 36.88108), Point::from(stop));
+}
+
+#[test]
+fn test_shape_line_string() {
+    let mut feed = FeedReader::new("./examples/good_feed").load().unwrap();
+    feed.shapes.reverse();
+    let line = feed.shape_line_string("shape_1").unwrap();
+    let expected: LineString<f64> = vec![(1.0, 1.0), (4.0, 2.0), (9.0, 3.0), (16.0, 4.0)].into();
+    assert_eq!(expected, line);
+    assert!(feed.shape_line_string("shape_0").is_none());
+
+    let shapes = feed.shape_line_strings();
+    let ids: Vec<&str> = shapes.iter().map(|(id, _)| *id).collect();
+    assert_eq!(vec!["shape_1", "shape_2", "shape_3"], ids);
+    assert_eq!(expected, shapes[0].1);
+}
+
+#[test]
+fn test_feed_bounding_rect() {
+    let mut feed = FeedReader::new("./examples/good_feed").load().unwrap();
+    let stops = feed.stops_bounding_rect().unwrap();
+    assert_eq!(coord! { x: -117.133162, y: 36.425288 }, stops.min());
+    assert_eq!(coord! { x: -116.40094, y: 36.915682 }, stops.max());
+    let shapes = feed.shapes_bounding_rect().unwrap();
+    assert_eq!(coord! { x: 1.0, y: 1.0 }, shapes.min());
+    assert_eq!(coord! { x: 36.0, y: 24.0 }, shapes.max());
+
+    let all = feed.bounding_rect().unwrap();
+    assert_eq!(coord! { x: -117.133162, y: 1.0 }, all.min());
+    assert_eq!(coord! { x: 36.0, y: 36.915682 }, all.max());
+
+    feed.stops.clear();
+    feed.shapes.clear();
+    assert!(feed.bounding_rect().is_none());
+}
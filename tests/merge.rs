@@ -0,0 +1,179 @@
+mod common;
+
+use chrono::NaiveDate;
+use transitfeed::{Collision, Feed, FeedMerger};
+
+use common::good_feed;
+
+fn counts(feed: &Feed) -> Vec<usize> {
+    vec![
+        feed.agencies.len(),
+        feed.stops.len(),
+        feed.routes.len(),
+        feed.trips.len(),
+        feed.stop_times.len(),
+        feed.calendars.len(),
+        feed.calendar_dates.len(),
+        feed.shapes.len(),
+        feed.frequencies.len(),
+        feed.transfers.len(),
+        feed.fare_attributes.len(),
+        feed.fare_rules.len(),
+    ]
+}
+
+#[test]
+fn test_merge_identical_feeds() {
+    let mut merger = FeedMerger::new(Collision::Identical);
+    merger.add(good_feed(), "a_").unwrap();
+    merger.add(good_feed(), "b_").unwrap();
+    let feed = merger.into_feed();
+    assert_eq!(counts(&good_feed()), counts(&feed));
+    assert!(feed.stops.iter().all(|s| !s.stop_id.starts_with("b_")));
+}
+
+#[test]
+fn test_merge_prefix() {
+    let mut merger = FeedMerger::new(Collision::Prefix);
+    merger.add(good_feed(), "a_").unwrap();
+    merger.add(good_feed(), "b_").unwrap();
+    let feed = merger.into_feed();
+    let single = counts(&good_feed());
+    let doubled: Vec<usize> = single.iter().map(|n| n * 2).collect();
+    assert_eq!(doubled, counts(&feed));
+    // Only the second feed collides
+    assert!(feed.routes.iter().any(|r| r.route_id == "AB"));
+    let route = feed.routes.iter().find(|r| r.route_id == "b_AB").unwrap();
+    assert_eq!(Some("b_DTA"), route.agency_id.as_deref());
+    let trip = feed.trips.iter().find(|t| t.trip_id == "b_AB1").unwrap();
+    assert_eq!("b_AB", trip.route_id);
+    assert_eq!("b_FULLW", trip.service_id);
+    let stop = feed
+        .stops
+        .iter()
+        .find(|s| s.stop_id == "b_BEATTY_AIRPORT")
+        .unwrap();
+    assert_eq!(
+        Some("b_BEATTY_AIRPORT_STATION"),
+        stop.parent_station.as_deref()
+    );
+    assert!(feed
+        .stop_times
+        .iter()
+        .filter(|st| st.trip_id.starts_with("b_"))
+        .all(|st| st.stop_id.starts_with("b_")));
+}
+
+#[test]
+fn test_merge_prefix_all() {
+    let mut merger = FeedMerger::new(Collision::PrefixAll);
+    merger.add(good_feed(), "a_").unwrap();
+    let feed = merger.into_feed();
+    assert_eq!(counts(&good_feed()), counts(&feed));
+    assert!(feed.stops.iter().all(|s| s.stop_id.starts_with("a_")));
+    assert!(feed.trips.iter().all(|t| t.route_id.starts_with("a_")));
+    assert!(feed.fare_rules.iter().all(|r| r.fare_id.starts_with("a_")));
+}
+
+#[test]
+fn test_merge_moved_stop() {
+    let mut moved = good_feed();
+    for stop in moved.stops.iter_mut() {
+        match stop.stop_id.as_str() {
+            // About 5 m, still the same stop
+            "STAGECOACH" => stop.stop_lat += 0.000_05,
+            // About 1 km away
+            "BULLFROG" => stop.stop_lat += 0.01,
+            _ => {}
+        }
+    }
+    let mut merger = FeedMerger::new(Collision::Identical);
+    merger.add(good_feed(), "a_").unwrap();
+    merger.add(moved, "b_").unwrap();
+    let feed = merger.into_feed();
+
+    let stops: Vec<&str> = feed.stops.iter().map(|s| s.stop_id.as_str()).collect();
+    assert_eq!(11, stops.len());
+    assert!(stops.contains(&"b_BULLFROG") && !stops.contains(&"b_STAGECOACH"));
+    // Trips serving BULLFROG now differ and are kept under new IDs
+    let mut prefixed: Vec<&str> = feed
+        .trips
+        .iter()
+        .map(|t| t.trip_id.as_str())
+        .filter(|id| id.starts_with("b_"))
+        .collect();
+    prefixed.sort_unstable();
+    assert_eq!(vec!["b_AB1", "b_AB2", "b_BFC1", "b_BFC2"], prefixed);
+    assert_eq!(good_feed().routes.len(), feed.routes.len());
+}
+
+#[test]
+fn test_merge_feed_info() {
+    let date = |d| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+    let mut first = good_feed();
+    first.feed_info[0].feed_start_date = Some(date("2020-01-01"));
+    first.feed_info[0].feed_end_date = Some(date("2020-06-30"));
+    let mut second = good_feed();
+    second.feed_info[0].feed_start_date = Some(date("2020-03-01"));
+    second.feed_info[0].feed_end_date = Some(date("2020-12-31"));
+
+    let mut merger = FeedMerger::new(Collision::Identical);
+    merger.add(first, "a_").unwrap();
+    merger.add(second, "b_").unwrap();
+    let feed = merger.into_feed();
+    assert_eq!(1, feed.feed_info.len());
+    assert_eq!(Some(date("2020-01-01")), feed.feed_info[0].feed_start_date);
+    assert_eq!(Some(date("2020-12-31")), feed.feed_info[0].feed_end_date);
+}
+
+#[test]
+fn test_merge_prefix_clear_of_added_ids() {
+    // The added feed already uses the ID its BULLFROG would be prefixed to
+    let mut added = good_feed();
+    for stop in added.stops.iter_mut() {
+        if stop.stop_id == "STAGECOACH" {
+            stop.stop_id = "b_BULLFROG".to_string();
+        }
+    }
+    for stop_time in added.stop_times.iter_mut() {
+        if stop_time.stop_id == "STAGECOACH" {
+            stop_time.stop_id = "b_BULLFROG".to_string();
+        }
+    }
+    let mut merger = FeedMerger::new(Collision::Prefix);
+    merger.add(good_feed(), "a_").unwrap();
+    merger.add(added, "b_").unwrap();
+    let feed = merger.into_feed();
+
+    let mut stops: Vec<&str> = feed.stops.iter().map(|s| s.stop_id.as_str()).collect();
+    stops.sort_unstable();
+    let count = stops.len();
+    stops.dedup();
+    assert_eq!(count, stops.len());
+    assert!(stops.contains(&"b_b_BULLFROG"));
+    // STBA starts at STAGECOACH, AB1 stops at BULLFROG
+    let stop_at = |trip_id: &str, sequence| {
+        feed.stop_times
+            .iter()
+            .find(|st| st.trip_id == trip_id && st.stop_sequence == sequence)
+            .map(|st| st.stop_id.as_str())
+    };
+    assert_eq!(Some("b_BULLFROG"), stop_at("b_STBA", 0));
+    assert_eq!(Some("b_b_BULLFROG"), stop_at("b_AB1", 2));
+}
+
+#[test]
+fn test_merge_without_prefix() {
+    let mut merger = FeedMerger::new(Collision::Prefix);
+    merger.add(good_feed(), "").unwrap();
+    assert!(merger.add(good_feed(), "").is_err());
+    // Nothing of the rejected feed is merged
+    assert_eq!(counts(&good_feed()), counts(&merger.into_feed()));
+
+    let mut merger = FeedMerger::new(Collision::Identical);
+    merger.add(good_feed(), "").unwrap();
+    merger.add(good_feed(), "").unwrap();
+    let mut moved = good_feed();
+    moved.stops[0].stop_lat += 0.01;
+    assert!(merger.add(moved, "").is_err());
+}
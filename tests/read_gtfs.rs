@@ -1,6 +1,6 @@
 use transitfeed::{
     Agency, Calendar, CalendarDate, FareAttribute, FareRule, FeedInfo, Frequency, GTFSIterator,
-    Route, ShapePoint, Stop, StopTime, Transfer, Trip,
+    GTFSRecordReader, Route, ShapePoint, Stop, StopTime, StopTimeRef, Transfer, Trip,
 };
 
 #[test]
@@ -48,6 +48,19 @@ fn test_read_stop_times() {
     }
 }
 
+#[test]
+fn test_read_stop_time_records() {
+    let iter: GTFSIterator<_, StopTime> =
+        GTFSIterator::from_path("./examples/good_feed/stop_times.txt").unwrap();
+    let mut records = GTFSRecordReader::from_path("./examples/good_feed/stop_times.txt").unwrap();
+    for expected in iter {
+        let result: Result<StopTimeRef, _> = records.read().unwrap();
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        assert_eq!(expected.unwrap(), StopTime::from(result.unwrap()));
+    }
+    assert!(records.read::<StopTimeRef>().is_none());
+}
+
 #[test]
 fn test_read_calendar() {
     let iter: GTFSIterator<_, Calendar> =
@@ -0,0 +1,422 @@
+#![cfg(feature = "realtime")]
+use chrono::{FixedOffset, NaiveDate};
+use std::fs;
+use transitfeed::realtime::{
+    alert, decode, encode, nyct_trip_descriptor, to_json, trip_descriptor, trip_update,
+    vehicle_position, Alert, EntitySelector, FeedMessage, Notice, NoticeKind, Position,
+    PredictionStatus, Schedule, TranslatedString, TripDescriptor, TripUpdate, Validator,
+    VehiclePosition,
+};
+use transitfeed::FeedReader;
+
+// Midnight of 2024-06-05 in America/Los_Angeles
+const SERVICE_DAY: i64 = 1717570800;
+
+fn pdt() -> FixedOffset {
+    FixedOffset::west_opt(7 * 60 * 60).unwrap()
+}
+
+#[test]
+fn test_decode_trip_updates() {
+    let message = decode(&fs::read("./examples/realtime/trip_updates.pb").unwrap()).unwrap();
+    assert_eq!("2.0", message.header.gtfs_realtime_version);
+    assert_eq!(Some(1717599720), message.header.timestamp);
+    assert_eq!(3, message.trip_updates().count());
+
+    let ab1 = message.trip_updates().next().unwrap();
+    assert_eq!(Some("AB1"), ab1.trip.trip_id.as_deref());
+    assert_eq!(Some("20240605"), ab1.trip.start_date.as_deref());
+    let updates = &ab1.stop_time_update;
+    assert_eq!(Some(120), updates[0].departure.as_ref().unwrap().delay);
+    assert_eq!(Some(300), updates[1].arrival.as_ref().unwrap().delay);
+
+    let canceled = message.trip_updates().nth(1).unwrap();
+    assert_eq!(
+        trip_descriptor::ScheduleRelationship::Canceled,
+        canceled.trip.schedule_relationship()
+    );
+
+    let skipped = &message.trip_updates().nth(2).unwrap().stop_time_update[0];
+    assert_eq!(
+        trip_update::stop_time_update::ScheduleRelationship::Skipped,
+        skipped.schedule_relationship()
+    );
+}
+
+#[test]
+fn test_decode_nyct_extensions() {
+    let message = decode(&fs::read("./examples/realtime/trip_updates.pb").unwrap()).unwrap();
+    let header = message.header.nyct_feed_header.as_ref().unwrap();
+    assert_eq!("1.0", header.nyct_subway_version);
+
+    let ab1 = message.trip_updates().next().unwrap();
+    let descriptor = ab1.trip.nyct_trip_descriptor.as_ref().unwrap();
+    assert_eq!(Some("1 0800 BEA/BUL"), descriptor.train_id.as_deref());
+    assert_eq!(
+        nyct_trip_descriptor::Direction::North,
+        descriptor.direction()
+    );
+    let track = ab1.stop_time_update[1].nyct_stop_time_update.as_ref();
+    assert_eq!(Some("2"), track.unwrap().actual_track.as_deref());
+}
+
+#[test]
+fn test_decode_vehicle_positions() {
+    let message = decode(&fs::read("./examples/realtime/vehicle_positions.pb").unwrap()).unwrap();
+    let vehicle = message.vehicle_positions().next().unwrap();
+    let position = vehicle.position.as_ref().unwrap();
+    assert!((position.latitude - 36.868446).abs() < 1e-5);
+    assert_eq!(Some("BEATTY_AIRPORT"), vehicle.stop_id.as_deref());
+    assert_eq!(
+        vehicle_position::VehicleStopStatus::StoppedAt,
+        vehicle.current_status()
+    );
+    assert_eq!(
+        vehicle_position::OccupancyStatus::ManySeatsAvailable,
+        vehicle.occupancy_status()
+    );
+    assert_eq!(Some(20), vehicle.occupancy_percentage);
+}
+
+#[test]
+fn test_decode_alerts() {
+    let message = decode(&fs::read("./examples/realtime/alerts.pb").unwrap()).unwrap();
+    let alert = message.alerts().next().unwrap();
+    assert_eq!(alert::Cause::Construction, alert.cause());
+    assert_eq!(alert::Effect::Detour, alert.effect());
+    assert_eq!(alert::SeverityLevel::Warning, alert.severity_level());
+    assert_eq!(
+        Some("Bullfrog stop moved"),
+        alert.header_text.as_ref().unwrap().text(Some("en"))
+    );
+    assert_eq!(
+        Some("BULLFROG"),
+        alert.informed_entity[1].stop_id.as_deref()
+    );
+}
+
+#[test]
+fn test_decode_invalid_message() {
+    let result = decode(&fs::read("./examples/good_feed/agency.txt").unwrap());
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_predict_delays() {
+    let feed = FeedReader::new("./examples/good_feed").load().unwrap();
+    let schedule = Schedule::from_feed(&feed);
+    let message = decode(&fs::read("./examples/realtime/trip_updates.pb").unwrap()).unwrap();
+    let ab1 = message.trip_updates().next().unwrap();
+
+    let prediction = schedule.predict(ab1, &pdt()).unwrap();
+    assert_eq!("AB1", prediction.trip_id);
+    let beatty = &prediction.stop_times[0];
+    assert_eq!(Some(SERVICE_DAY + 8 * 3600), beatty.scheduled_departure);
+    assert_eq!(Some(120), beatty.departure_delay());
+    assert_eq!(PredictionStatus::Predicted, beatty.status);
+    let bullfrog = &prediction.stop_times[1];
+    assert_eq!(Some(SERVICE_DAY + 8 * 3600 + 15 * 60), bullfrog.arrival);
+    assert_eq!(Some(SERVICE_DAY + 8 * 3600 + 20 * 60), bullfrog.departure);
+}
+
+#[test]
+fn test_predict_canceled_and_skipped() {
+    let feed = FeedReader::new("./examples/good_feed").load().unwrap();
+    let schedule = Schedule::from_feed(&feed);
+    let message = decode(&fs::read("./examples/realtime/trip_updates.pb").unwrap()).unwrap();
+
+    let canceled = schedule
+        .predict(message.trip_updates().nth(1).unwrap(), &pdt())
+        .unwrap();
+    assert!(canceled
+        .stop_times
+        .iter()
+        .all(|st| st.status == PredictionStatus::Canceled && st.arrival.is_none()));
+
+    let city2 = schedule
+        .predict(message.trip_updates().nth(2).unwrap(), &pdt())
+        .unwrap();
+    let statuses: Vec<_> = city2.stop_times.iter().map(|st| st.status).collect();
+    assert_eq!(
+        vec![
+            PredictionStatus::Scheduled,
+            PredictionStatus::Skipped,
+            PredictionStatus::Scheduled,
+            PredictionStatus::Predicted,
+            PredictionStatus::Propagated,
+        ],
+        statuses
+    );
+    assert_eq!(Some(60), city2.stop_times[4].arrival_delay());
+}
+
+fn update(trip_id: &str, relationship: trip_descriptor::ScheduleRelationship) -> TripUpdate {
+    TripUpdate {
+        trip: TripDescriptor {
+            trip_id: Some(trip_id.to_string()),
+            start_date: Some("20240605".to_string()),
+            schedule_relationship: Some(relationship as i32),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_predict_no_data() {
+    let feed = FeedReader::new("./examples/good_feed").load().unwrap();
+    let schedule = Schedule::from_feed(&feed);
+    let mut city1 = update("CITY1", trip_descriptor::ScheduleRelationship::Scheduled);
+    city1.stop_time_update = vec![
+        trip_update::StopTimeUpdate {
+            stop_sequence: Some(0),
+            departure: Some(trip_update::StopTimeEvent {
+                delay: Some(90),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        trip_update::StopTimeUpdate {
+            stop_sequence: Some(10),
+            schedule_relationship: Some(
+                trip_update::stop_time_update::ScheduleRelationship::NoData as i32,
+            ),
+            ..Default::default()
+        },
+    ];
+
+    let prediction = schedule.predict(&city1, &pdt()).unwrap();
+    let statuses: Vec<_> = prediction.stop_times.iter().map(|st| st.status).collect();
+    assert_eq!(
+        vec![
+            PredictionStatus::Predicted,
+            PredictionStatus::Propagated,
+            PredictionStatus::NoData,
+            PredictionStatus::NoData,
+            PredictionStatus::NoData,
+        ],
+        statuses
+    );
+    assert_eq!(Some(90), prediction.stop_times[1].arrival_delay());
+}
+
+#[test]
+fn test_predict_added_and_duplicated_trips() {
+    let feed = FeedReader::new("./examples/good_feed").load().unwrap();
+    let schedule = Schedule::from_feed(&feed);
+
+    let mut duplicated = update("AB1", trip_descriptor::ScheduleRelationship::Duplicated);
+    duplicated.trip_properties = Some(trip_update::TripProperties {
+        trip_id: Some("AB1-extra".to_string()),
+        start_time: Some("10:00:00".to_string()),
+        ..Default::default()
+    });
+    let prediction = schedule.predict(&duplicated, &pdt()).unwrap();
+    assert_eq!("AB1-extra", prediction.trip_id);
+    assert_eq!(
+        Some(SERVICE_DAY + 10 * 3600 + 10 * 60),
+        prediction.stop_times[1].arrival
+    );
+
+    let mut added = update("EXTRA1", trip_descriptor::ScheduleRelationship::Added);
+    added.stop_time_update = vec![trip_update::StopTimeUpdate {
+        stop_id: Some("AMV".to_string()),
+        arrival: Some(trip_update::StopTimeEvent {
+            time: Some(SERVICE_DAY + 9 * 3600),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }];
+    let prediction = schedule.predict(&added, &pdt()).unwrap();
+    assert_eq!("AMV", prediction.stop_times[0].stop_id);
+    assert_eq!(
+        Some(SERVICE_DAY + 9 * 3600),
+        prediction.stop_times[0].departure
+    );
+    assert_eq!(None, prediction.stop_times[0].arrival_delay());
+
+    assert!(schedule
+        .predict(
+            &update("MISSING", trip_descriptor::ScheduleRelationship::Scheduled),
+            &pdt()
+        )
+        .is_err());
+}
+
+fn built_message() -> FeedMessage {
+    let feed = FeedReader::new("./examples/good_feed").load().unwrap();
+    let date = NaiveDate::from_ymd_opt(2024, 6, 5).unwrap();
+    let trip = feed.trips.iter().find(|t| t.trip_id == "AB1").unwrap();
+    let stop_time = feed
+        .stop_times
+        .iter()
+        .find(|st| st.trip_id == "AB1")
+        .unwrap();
+
+    let mut message = FeedMessage::new(1717599720);
+    let mut update = trip_update::StopTimeUpdate::for_stop_time(stop_time);
+    update.departure = Some(trip_update::StopTimeEvent {
+        delay: Some(120),
+        ..Default::default()
+    });
+    message.push_trip_update(
+        "AB1",
+        TripUpdate {
+            trip: TripDescriptor::for_trip(trip, date),
+            stop_time_update: vec![update],
+            ..Default::default()
+        },
+    );
+    let stop = feed.stops.iter().find(|s| s.stop_id == "BULLFROG").unwrap();
+    message.push_alert(
+        "bullfrog",
+        Alert {
+            informed_entity: vec![EntitySelector::for_stop(stop)],
+            effect: Some(alert::Effect::Detour as i32),
+            header_text: Some(TranslatedString::new("Bullfrog stop moved")),
+            ..Default::default()
+        },
+    );
+    message
+}
+
+#[test]
+fn test_encode_round_trip() {
+    let message = built_message();
+    let decoded = decode(&encode(&message)).unwrap();
+    assert_eq!(message, decoded);
+
+    let ab1 = decoded.trip_updates().next().unwrap();
+    assert_eq!(Some("AB"), ab1.trip.route_id.as_deref());
+    assert_eq!(Some("20240605"), ab1.trip.start_date.as_deref());
+    assert_eq!(Some(1), ab1.stop_time_update[0].stop_sequence);
+    assert_eq!(
+        Some("BEATTY_AIRPORT"),
+        ab1.stop_time_update[0].stop_id.as_deref()
+    );
+    let alert = decoded.alerts().next().unwrap();
+    assert_eq!(
+        Some("Bullfrog stop moved"),
+        alert.header_text.as_ref().unwrap().text(None)
+    );
+}
+
+#[test]
+fn test_to_json() {
+    let json = to_json(&built_message()).unwrap();
+    assert!(json.contains(r#""gtfsRealtimeVersion": "2.0""#));
+    assert!(json.contains(r#""incrementality": "FULL_DATASET""#));
+    assert!(json.contains(r#""timestamp": "1717599720""#));
+    assert!(json.contains(r#""tripId": "AB1""#));
+    assert!(json.contains(r#""effect": "DETOUR""#));
+    assert!(!json.contains("vehicle"));
+
+    let decoded = decode(&fs::read("./examples/realtime/trip_updates.pb").unwrap()).unwrap();
+    let json = to_json(&decoded).unwrap();
+    assert!(json.contains(r#""[transit_realtime.nyct_trip_descriptor]""#));
+    assert!(json.contains(r#""scheduleRelationship": "CANCELED""#));
+}
+
+#[test]
+fn test_validate_fixtures() {
+    let feed = FeedReader::new("./examples/good_feed").load().unwrap();
+    let validator = Validator::new(&feed, pdt());
+    for fixture in ["trip_updates", "vehicle_positions", "alerts"].iter() {
+        let path = format!("./examples/realtime/{}.pb", fixture);
+        let message = decode(&fs::read(path).unwrap()).unwrap();
+        let now = message.header.timestamp.unwrap();
+        assert_eq!(Vec::<Notice>::new(), validator.validate(&message, now));
+    }
+}
+
+#[test]
+fn test_validate_notices() {
+    let feed = FeedReader::new("./examples/good_feed").load().unwrap();
+    let validator = Validator::new(&feed, pdt());
+    let now = (SERVICE_DAY + 8 * 3600) as u64;
+    let stop_time_update =
+        |sequence, stop_id: Option<&str>, arrival: Option<i64>| trip_update::StopTimeUpdate {
+            stop_sequence: Some(sequence),
+            stop_id: stop_id.map(str::to_string),
+            arrival: arrival.map(|time| trip_update::StopTimeEvent {
+                time: Some(time),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+    let mut message = FeedMessage::new(now - 30);
+    let scheduled = trip_descriptor::ScheduleRelationship::Scheduled;
+    message.push_trip_update("unknown", update("XX", scheduled));
+    let mut stops = update("AB1", scheduled);
+    stops.stop_time_update = vec![
+        stop_time_update(2, Some("FUR_CREEK_RES"), None),
+        stop_time_update(9, None, None),
+    ];
+    message.push_trip_update("stops", stops);
+    let mut times = update("AB1", scheduled);
+    times.timestamp = Some(now - 600);
+    times.stop_time_update = vec![
+        stop_time_update(1, None, Some(SERVICE_DAY + 9 * 3600)),
+        stop_time_update(2, None, Some(SERVICE_DAY + 8 * 3600 + 30 * 60)),
+    ];
+    message.push_trip_update("times", times);
+    message.push_vehicle_position(
+        "bus",
+        VehiclePosition {
+            trip: Some(update("AB1", scheduled).trip),
+            position: Some(Position {
+                latitude: 36.42529,
+                longitude: -117.13316,
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+    );
+
+    let notices = validator.validate(&message, now);
+    let kinds: Vec<_> = notices.iter().map(|n| &n.kind).collect();
+    assert_eq!(6, kinds.len());
+    assert_eq!(
+        &NoticeKind::UnknownTrip {
+            trip_id: "XX".to_string()
+        },
+        kinds[0]
+    );
+    assert_eq!(
+        &NoticeKind::StopNotOnTrip {
+            trip_id: "AB1".to_string(),
+            stop_id: "FUR_CREEK_RES".to_string()
+        },
+        kinds[1]
+    );
+    assert_eq!(
+        &NoticeKind::UnknownStopSequence {
+            trip_id: "AB1".to_string(),
+            stop_sequence: 9
+        },
+        kinds[2]
+    );
+    assert_eq!(
+        &NoticeKind::NonMonotonicTimes {
+            trip_id: "AB1".to_string(),
+            stop_sequence: 2
+        },
+        kinds[3]
+    );
+    assert_eq!(
+        &NoticeKind::StaleTimestamp {
+            timestamp: now - 600,
+            age: 600
+        },
+        kinds[4]
+    );
+    match kinds[5] {
+        NoticeKind::VehicleOffShape { distance, .. } => assert!(*distance > 40_000.0),
+        kind => panic!("unexpected notice {:?}", kind),
+    }
+    assert_eq!(
+        "entity stops - stop FUR_CREEK_RES is not on trip AB1",
+        format!("{}", notices[1])
+    );
+}
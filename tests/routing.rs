@@ -0,0 +1,380 @@
+mod common;
+
+use chrono::{Duration, NaiveDate};
+use transitfeed::routing::{ConnectionScan, FootpathGenerator, Hull, Leg, Raptor};
+use transitfeed::{TimeOffset, Transfer, TransferType};
+
+use common::good_feed;
+
+fn wednesday() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 6, 5).unwrap()
+}
+
+fn transfer(from: &str, to: &str, transfer_type: TransferType, minutes: i64) -> Transfer {
+    Transfer {
+        from_stop_id: from.to_string(),
+        to_stop_id: to.to_string(),
+        transfer_type,
+        min_transfer_time: Some(Duration::minutes(minutes)),
+    }
+}
+
+fn trip_ids(legs: &[Leg]) -> Vec<String> {
+    legs.iter()
+        .map(|leg| match leg {
+            Leg::Transit { trip, .. } => trip.trip_id.clone(),
+            Leg::Walk { to, .. } => format!("walk to {}", to.stop_id),
+        })
+        .collect()
+}
+
+#[test]
+fn test_raptor_direct_trip() {
+    let feed = good_feed();
+    let raptor = Raptor::new(&feed, wednesday());
+    let journey = raptor
+        .earliest_arrival("BEATTY_AIRPORT", "BULLFROG", TimeOffset::from_hms(7, 0, 0))
+        .unwrap();
+    assert_eq!(vec!["AB1"], trip_ids(&journey.legs));
+    assert_eq!(TimeOffset::from_hms(8, 0, 0), journey.departure);
+    assert_eq!(TimeOffset::from_hms(8, 10, 0), journey.arrival);
+    assert_eq!(0, journey.transfers());
+
+    assert!(raptor
+        .earliest_arrival("BEATTY_AIRPORT", "BULLFROG", TimeOffset::from_hms(8, 1, 0))
+        .is_none());
+    assert!(raptor
+        .earliest_arrival("BEATTY_AIRPORT", "NOWHERE", TimeOffset::from_hms(7, 0, 0))
+        .is_none());
+}
+
+#[test]
+fn test_raptor_transfer() {
+    let feed = good_feed();
+    let mut raptor = Raptor::new(&feed, wednesday());
+    let journey = raptor
+        .earliest_arrival(
+            "BEATTY_AIRPORT",
+            "FUR_CREEK_RES",
+            TimeOffset::from_hms(7, 0, 0),
+        )
+        .unwrap();
+    assert_eq!(vec!["AB1", "BFC1"], trip_ids(&journey.legs));
+    assert_eq!(TimeOffset::from_hms(9, 20, 0), journey.arrival);
+    assert_eq!(1, journey.transfers());
+
+    raptor.max_transfers = 0;
+    assert!(raptor
+        .earliest_arrival(
+            "BEATTY_AIRPORT",
+            "FUR_CREEK_RES",
+            TimeOffset::from_hms(7, 0, 0)
+        )
+        .is_none());
+}
+
+#[test]
+fn test_raptor_change_times() {
+    let mut feed = good_feed();
+    feed.transfers.push(transfer(
+        "BULLFROG",
+        "BULLFROG",
+        TransferType::MinimumTime,
+        15,
+    ));
+    let raptor = Raptor::new(&feed, wednesday());
+    assert!(raptor
+        .earliest_arrival(
+            "BEATTY_AIRPORT",
+            "FUR_CREEK_RES",
+            TimeOffset::from_hms(7, 0, 0)
+        )
+        .is_none());
+
+    let mut feed = good_feed();
+    feed.transfers.push(transfer(
+        "BULLFROG",
+        "BULLFROG",
+        TransferType::NotPossible,
+        0,
+    ));
+    let raptor = Raptor::new(&feed, wednesday());
+    assert!(raptor
+        .earliest_arrival(
+            "BEATTY_AIRPORT",
+            "FUR_CREEK_RES",
+            TimeOffset::from_hms(7, 0, 0)
+        )
+        .is_none());
+}
+
+#[test]
+fn test_raptor_walks() {
+    let feed = good_feed();
+    let raptor = Raptor::new(&feed, wednesday());
+    // transfers.txt links EMSI to NANAA with 20 minutes
+    let journey = raptor
+        .earliest_arrival("EMSI", "NANAA", TimeOffset::from_hms(6, 0, 0))
+        .unwrap();
+    assert_eq!(vec!["walk to NANAA"], trip_ids(&journey.legs));
+    assert_eq!(TimeOffset::from_hms(6, 0, 0), journey.departure);
+    assert_eq!(TimeOffset::from_hms(6, 20, 0), journey.arrival);
+}
+
+#[test]
+fn test_raptor_pareto() {
+    let mut feed = good_feed();
+    feed.transfers.push(transfer(
+        "BEATTY_AIRPORT",
+        "FUR_CREEK_RES",
+        TransferType::MinimumTime,
+        4 * 60,
+    ));
+    let raptor = Raptor::new(&feed, wednesday());
+    let journeys = raptor.pareto(
+        "BEATTY_AIRPORT",
+        "FUR_CREEK_RES",
+        TimeOffset::from_hms(7, 0, 0),
+    );
+    assert_eq!(2, journeys.len());
+    assert_eq!(vec!["walk to FUR_CREEK_RES"], trip_ids(&journeys[0].legs));
+    assert_eq!(TimeOffset::from_hms(11, 0, 0), journeys[0].arrival);
+    assert_eq!(vec!["AB1", "BFC1"], trip_ids(&journeys[1].legs));
+    assert_eq!(TimeOffset::from_hms(9, 20, 0), journeys[1].arrival);
+}
+
+#[test]
+fn test_raptor_after_midnight() {
+    let mut feed = good_feed();
+    for stop_time in feed.stop_times.iter_mut().filter(|st| st.trip_id == "AB1") {
+        stop_time.arrival_time.hours += 16;
+        stop_time.departure_time.hours += 16;
+    }
+    let thursday = wednesday().succ_opt().unwrap();
+    let raptor = Raptor::new(&feed, thursday);
+    let journey = raptor
+        .earliest_arrival("BEATTY_AIRPORT", "BULLFROG", TimeOffset::from_hms(0, 0, 0))
+        .unwrap();
+    assert_eq!(TimeOffset::from_hms(0, 10, 0), journey.arrival);
+    match &journey.legs[0] {
+        Leg::Transit { service_date, .. } => assert_eq!(wednesday(), *service_date),
+        leg => panic!("unexpected leg {:?}", leg),
+    }
+}
+
+#[test]
+fn test_csa_profile() {
+    let feed = good_feed();
+    let saturday = NaiveDate::from_ymd_opt(2024, 6, 8).unwrap();
+    let csa = ConnectionScan::new(&feed, saturday);
+    let journeys = csa.profile("BEATTY_AIRPORT", "AMV");
+    let times: Vec<_> = journeys
+        .iter()
+        .map(|j| (j.departure.clone(), j.arrival.clone()))
+        .collect();
+    assert_eq!(
+        vec![
+            (TimeOffset::from_hms(8, 0, 0), TimeOffset::from_hms(9, 0, 0)),
+            (
+                TimeOffset::from_hms(13, 0, 0),
+                TimeOffset::from_hms(14, 0, 0)
+            ),
+        ],
+        times
+    );
+    assert_eq!(vec!["AAMV3"], trip_ids(&journeys[1].legs));
+
+    let csa = ConnectionScan::new(&feed, wednesday());
+    assert!(csa.profile("BEATTY_AIRPORT", "AMV").is_empty());
+    let journeys = csa.profile("BEATTY_AIRPORT", "FUR_CREEK_RES");
+    assert_eq!(1, journeys.len());
+    assert_eq!(vec!["AB1", "BFC1"], trip_ids(&journeys[0].legs));
+    assert_eq!(TimeOffset::from_hms(9, 20, 0), journeys[0].arrival);
+}
+
+#[test]
+fn test_csa_profile_after_midnight() {
+    let mut feed = good_feed();
+    for stop_time in feed.stop_times.iter_mut().filter(|st| st.trip_id == "AB1") {
+        let (arrival, departure) = match stop_time.stop_sequence {
+            1 => ((23, 50, 0), (23, 50, 0)),
+            2 => ((24, 10, 0), (24, 15, 0)),
+            _ => ((24, 30, 0), (24, 30, 0)),
+        };
+        stop_time.arrival_time = TimeOffset::from_hms(arrival.0, arrival.1, arrival.2);
+        stop_time.departure_time = TimeOffset::from_hms(departure.0, departure.1, departure.2);
+    }
+    let thursday = wednesday().succ_opt().unwrap();
+    let csa = ConnectionScan::new(&feed, thursday);
+    // Wednesday's AB1 reaches BULLFROG on Thursday but left on Wednesday
+    let journeys = csa.profile("BEATTY_AIRPORT", "BULLFROG");
+    assert_eq!(1, journeys.len());
+    assert_eq!(TimeOffset::from_hms(23, 50, 0), journeys[0].departure);
+    assert_eq!(TimeOffset::from_hms(24, 10, 0), journeys[0].arrival);
+}
+
+#[test]
+fn test_csa_profile_with_walks() {
+    let feed = good_feed();
+    let csa = ConnectionScan::new(&feed, wednesday());
+    // Riding CITY1 to EMSI and walking to NANAA is faster than CITY2, but
+    // CITY2 leaves later
+    let journeys = csa.profile("DADAN", "NANAA");
+    assert_eq!(2, journeys.len());
+    assert_eq!(vec!["CITY1", "walk to NANAA"], trip_ids(&journeys[0].legs));
+    assert_eq!(TimeOffset::from_hms(6, 21, 0), journeys[0].departure);
+    assert_eq!(TimeOffset::from_hms(6, 46, 0), journeys[0].arrival);
+    assert_eq!(vec!["CITY2"], trip_ids(&journeys[1].legs));
+    assert_eq!(TimeOffset::from_hms(6, 49, 0), journeys[1].arrival);
+}
+
+#[test]
+fn test_csa_profile_change_times() {
+    let mut feed = good_feed();
+    feed.transfers.push(transfer(
+        "BULLFROG",
+        "BULLFROG",
+        TransferType::MinimumTime,
+        10,
+    ));
+    let csa = ConnectionScan::new(&feed, wednesday());
+    assert_eq!(1, csa.profile("BEATTY_AIRPORT", "FUR_CREEK_RES").len());
+
+    let mut feed = good_feed();
+    feed.transfers.push(transfer(
+        "BULLFROG",
+        "BULLFROG",
+        TransferType::MinimumTime,
+        15,
+    ));
+    let csa = ConnectionScan::new(&feed, wednesday());
+    assert!(csa.profile("BEATTY_AIRPORT", "FUR_CREEK_RES").is_empty());
+
+    let mut feed = good_feed();
+    feed.transfers.push(transfer(
+        "BULLFROG",
+        "BULLFROG",
+        TransferType::NotPossible,
+        0,
+    ));
+    let csa = ConnectionScan::new(&feed, wednesday());
+    assert!(csa.profile("BEATTY_AIRPORT", "FUR_CREEK_RES").is_empty());
+}
+
+#[test]
+fn test_generate_footpaths() {
+    let feed = good_feed();
+    let footpaths = FootpathGenerator::new(650.0, 1.4).generate(&feed);
+    let mut pairs: Vec<_> = footpaths
+        .iter()
+        .map(|f| (f.from.stop_id.as_str(), f.to.stop_id.as_str()))
+        .collect();
+    pairs.sort();
+    // NADAV to NANAA is not possible according to transfers.txt
+    assert_eq!(
+        vec![("DADAN", "NADAV"), ("NADAV", "DADAN"), ("NANAA", "NADAV")],
+        pairs
+    );
+    let nanaa = footpaths
+        .iter()
+        .find(|f| f.from.stop_id == "NANAA")
+        .unwrap();
+    assert!((nanaa.distance - 599.0).abs() < 1.0);
+    assert_eq!(Duration::seconds(428), nanaa.duration);
+
+    let transfer = nanaa.to_transfer();
+    assert_eq!("NADAV", transfer.to_stop_id);
+    assert!(matches!(transfer.transfer_type, TransferType::MinimumTime));
+}
+
+#[test]
+fn test_raptor_generated_footpaths() {
+    let mut feed = good_feed();
+    let footpaths = FootpathGenerator::new(900.0, 1.4).transfers(&feed);
+    feed.transfers.extend(footpaths);
+    let raptor = Raptor::new(&feed, wednesday());
+    let journey = raptor
+        .earliest_arrival("STAGECOACH", "DADAN", TimeOffset::from_hms(5, 50, 0))
+        .unwrap();
+    assert_eq!(vec!["CITY1", "walk to DADAN"], trip_ids(&journey.legs));
+    assert_eq!(TimeOffset::from_hms(6, 15, 11), journey.arrival);
+}
+
+#[test]
+fn test_isochrone() {
+    let feed = good_feed();
+    let raptor = Raptor::new(&feed, wednesday());
+    let departure = TimeOffset::from_hms(7, 0, 0);
+    let isochrone = raptor
+        .isochrone(
+            "BEATTY_AIRPORT",
+            departure.clone(),
+            Duration::minutes(75),
+            &[],
+        )
+        .unwrap();
+    let bullfrog = isochrone.get("BULLFROG").unwrap();
+    assert_eq!(TimeOffset::from_hms(8, 10, 0), bullfrog.arrival);
+    assert_eq!(Duration::minutes(70), bullfrog.travel_time);
+    assert!(isochrone.get("BEATTY_AIRPORT").is_some());
+    assert!(isochrone.get("FUR_CREEK_RES").is_none());
+    assert_eq!(2, isochrone.hull(Hull::Convex).len());
+
+    let isochrone = raptor
+        .isochrone(
+            "BEATTY_AIRPORT",
+            departure.clone(),
+            Duration::minutes(150),
+            &[],
+        )
+        .unwrap();
+    let fur_creek = isochrone.get("FUR_CREEK_RES").unwrap();
+    assert_eq!(TimeOffset::from_hms(9, 20, 0), fur_creek.arrival);
+    assert_eq!(3, isochrone.hull(Hull::Convex).len());
+    assert_eq!(3, isochrone.hull(Hull::Concave(2.0)).len());
+
+    assert!(raptor
+        .isochrone("NOWHERE", departure.clone(), Duration::minutes(150), &[])
+        .is_none());
+}
+
+#[test]
+fn test_isochrone_footpaths() {
+    let feed = good_feed();
+    let raptor = Raptor::new(&feed, wednesday());
+    // Between CITY1 leaving STAGECOACH and CITY2 getting there
+    let departure = TimeOffset::from_hms(6, 30, 0);
+    let isochrone = raptor
+        .isochrone("STAGECOACH", departure.clone(), Duration::minutes(15), &[])
+        .unwrap();
+    assert!(isochrone.get("NANAA").is_none());
+
+    let footpaths = FootpathGenerator::new(900.0, 1.4).generate(&feed);
+    let isochrone = raptor
+        .isochrone("STAGECOACH", departure, Duration::minutes(15), &footpaths)
+        .unwrap();
+    let nanaa = isochrone.get("NANAA").unwrap();
+    assert_eq!(Duration::seconds(625), nanaa.travel_time);
+    assert_eq!(TimeOffset::from_hms(6, 40, 25), nanaa.arrival);
+}
+
+#[test]
+#[cfg(feature = "geojson")]
+fn test_isochrone_geojson() {
+    let feed = good_feed();
+    let raptor = Raptor::new(&feed, wednesday());
+    let isochrone = raptor
+        .isochrone(
+            "BEATTY_AIRPORT",
+            TimeOffset::from_hms(7, 0, 0),
+            Duration::minutes(150),
+            &[],
+        )
+        .unwrap();
+    let points = isochrone.to_geojson_points();
+    assert!(points.contains(r#""stop_id":"FUR_CREEK_RES""#));
+    assert!(points.contains(r#""arrival":"09:20:00""#));
+    assert!(points.contains("[-117.133162,36.425288]"));
+    let hull = isochrone.to_geojson_hull(Hull::Convex);
+    assert!(hull.contains(r#""type":"Polygon""#));
+}
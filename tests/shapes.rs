@@ -0,0 +1,289 @@
+mod common;
+
+use transitfeed::shapes::{
+    densify, densify_shapes, shape_distances, simplify, simplify_shapes, write_shapes,
+    ProjectionProblem, ShapeGenerator, ShapeProjector,
+};
+use transitfeed::{Feed, ShapePoint};
+
+use common::good_feed;
+
+#[test]
+fn test_generate_shapes() {
+    let feed = good_feed();
+    let generated = ShapeGenerator::default().generate(&feed);
+    // AAMV1 and AAMV3 visit the same stops, as do AAMV2 and AAMV4
+    assert_eq!(9, generated.len());
+    let aamv = generated
+        .iter()
+        .find(|s| s.trip_ids.contains(&"AAMV1".to_string()))
+        .unwrap();
+    assert_eq!(vec!["AAMV1", "AAMV3"], aamv.trip_ids);
+    // shape_1 to shape_3 are taken by shapes.txt, generated ones don't clash
+    let shapes = ShapeGenerator::new("shape_").generate(&feed);
+    assert_eq!("shape_4", shapes[0].shape_id);
+
+    let city = generated.iter().find(|s| s.trip_ids == ["CITY1"]).unwrap();
+    assert_eq!(5, city.points.len());
+    assert_eq!(Some(0.0), city.points[0].shape_dist_traveled);
+    let distances: Vec<f64> = city
+        .points
+        .iter()
+        .map(|p| p.shape_dist_traveled.unwrap())
+        .collect();
+    assert!(distances.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(36.915682, city.points[0].shape_pt_lat);
+    assert_eq!(-116.751677, city.points[0].shape_pt_lon);
+}
+
+#[test]
+fn test_apply_generated_shapes() {
+    let mut feed = good_feed();
+    assert_eq!(9, ShapeGenerator::default().apply(&mut feed));
+    assert_eq!(12 + 24, feed.shapes.len());
+    assert!(feed.trips.iter().all(|t| t.shape_id.is_some()));
+
+    let stba = feed.trips.iter().find(|t| t.trip_id == "STBA").unwrap();
+    let shape = feed.shape(stba.shape_id.as_ref().unwrap());
+    let stop_times: Vec<_> = feed
+        .stop_times
+        .iter()
+        .filter(|st| st.trip_id == "STBA")
+        .collect();
+    assert_eq!(Some(0.0), stop_times[0].shape_dist_traveled);
+    assert_eq!(
+        shape[1].shape_dist_traveled,
+        stop_times[1].shape_dist_traveled
+    );
+    assert!(stop_times[1].shape_dist_traveled.unwrap() > 5_000.0);
+
+    // Nothing is left to generate
+    assert_eq!(0, ShapeGenerator::default().apply(&mut feed));
+}
+
+fn stop_position(feed: &Feed, stop_id: &str) -> (f64, f64) {
+    let stop = feed.stops.iter().find(|s| s.stop_id == stop_id).unwrap();
+    (stop.stop_lat, stop.stop_lon)
+}
+
+/// Sets the shape of `trip_id` to a line through `stop_ids`
+fn set_shape(feed: &mut Feed, trip_id: &str, shape_id: &str, stop_ids: &[&str]) {
+    for (i, stop_id) in stop_ids.iter().enumerate() {
+        let (lat, lon) = stop_position(feed, stop_id);
+        feed.shapes.push(ShapePoint {
+            shape_id: shape_id.to_string(),
+            shape_pt_lat: lat,
+            shape_pt_lon: lon,
+            shape_pt_sequence: i as u64,
+            shape_dist_traveled: None,
+        });
+    }
+    let trip = feed
+        .trips
+        .iter_mut()
+        .find(|t| t.trip_id == trip_id)
+        .unwrap();
+    trip.shape_id = Some(shape_id.to_string());
+}
+
+const CITY: [&str; 5] = ["STAGECOACH", "NANAA", "NADAV", "DADAN", "EMSI"];
+
+#[test]
+fn test_project_stops() {
+    let mut feed = good_feed();
+    set_shape(&mut feed, "CITY1", "city", &CITY);
+    let projections = ShapeProjector::default().project(&feed);
+    assert_eq!(1, projections.len());
+    let city = &projections[0];
+    assert_eq!("CITY1", city.trip.trip_id);
+    assert_eq!(0, city.problems().count());
+    let shape = feed.shape("city");
+    let along = shape_distances(&shape);
+    for (stop, expected) in city.stops.iter().zip(&along) {
+        assert!(stop.distance < 0.01);
+        assert!((stop.shape_dist_traveled - expected).abs() < 0.01);
+    }
+
+    let problems = ShapeProjector::default().apply(&mut feed);
+    assert!(problems.is_empty());
+    let emsi = feed
+        .stop_times
+        .iter()
+        .find(|st| st.trip_id == "CITY1" && st.stop_id == "EMSI")
+        .unwrap();
+    assert!((emsi.shape_dist_traveled.unwrap() - along[4]).abs() < 0.01);
+    assert_eq!(Some(along[4]), feed.shape("city")[4].shape_dist_traveled);
+    // Other trips are left alone
+    let stba = feed
+        .stop_times
+        .iter()
+        .find(|st| st.trip_id == "STBA")
+        .unwrap();
+    assert_eq!(Some(0.212), stba.shape_dist_traveled);
+}
+
+#[test]
+fn test_apply_clears_trips_left_out() {
+    let mut feed = good_feed();
+    set_shape(&mut feed, "CITY1", "city", &CITY);
+    set_shape(&mut feed, "CITY2", "city", &[]);
+    for stop_time in feed.stop_times.iter_mut() {
+        if stop_time.trip_id == "CITY2" {
+            stop_time.shape_dist_traveled = Some(1.5);
+            if stop_time.stop_sequence == 300 {
+                stop_time.stop_id = "UNKNOWN".to_string();
+            }
+        }
+    }
+    ShapeProjector::default().apply(&mut feed);
+    // CITY2 can't be projected, so its distances in the feed's units are
+    // dropped now the shape is in meters
+    assert!(feed
+        .stop_times
+        .iter()
+        .filter(|st| st.trip_id == "CITY2")
+        .all(|st| st.shape_dist_traveled.is_none()));
+    assert!(feed
+        .stop_times
+        .iter()
+        .filter(|st| st.trip_id == "CITY1")
+        .all(|st| st.shape_dist_traveled.is_some()));
+}
+
+#[test]
+fn test_project_stops_on_loop() {
+    let mut feed = good_feed();
+    let mut round_trip = CITY.to_vec();
+    round_trip.extend(CITY.iter().rev().skip(1));
+    set_shape(&mut feed, "CITY1", "loop", &round_trip);
+    set_shape(&mut feed, "CITY2", "loop", &[]);
+    let along = shape_distances(&feed.shape("loop"));
+
+    let projections = ShapeProjector::default().project(&feed);
+    let outbound: Vec<f64> = projections[0]
+        .stops
+        .iter()
+        .map(|s| s.shape_dist_traveled)
+        .collect();
+    let inbound: Vec<f64> = projections[1]
+        .stops
+        .iter()
+        .map(|s| s.shape_dist_traveled)
+        .collect();
+    assert_eq!(0, projections[1].problems().count());
+    for i in 0..5 {
+        assert!(
+            (outbound[i] - along[i]).abs() < 0.01,
+            "{} {:?} {:?}",
+            i,
+            outbound,
+            along
+        );
+        assert!((inbound[i] - along[i + 4]).abs() < 0.01);
+    }
+}
+
+#[test]
+fn test_projection_problems() {
+    let mut feed = good_feed();
+    set_shape(&mut feed, "CITY1", "city", &CITY);
+    set_shape(&mut feed, "CITY2", "city", &[]);
+    let nadav = feed
+        .stops
+        .iter_mut()
+        .find(|s| s.stop_id == "NADAV")
+        .unwrap();
+    nadav.stop_lat += 0.01;
+
+    let projections = ShapeProjector::default().project(&feed);
+    let outbound: Vec<_> = projections[0].problems().collect();
+    assert_eq!(1, outbound.len());
+    assert_eq!("NADAV", outbound[0].stop_time.stop_id);
+    assert_eq!(Some(ProjectionProblem::TooFar), outbound[0].problem);
+    assert!(outbound[0].distance > 1000.0);
+
+    // CITY2 runs against the direction of the shape
+    let inbound = &projections[1];
+    assert_eq!(4, inbound.problems().count());
+    assert!(inbound
+        .stops
+        .windows(2)
+        .all(|w| w[0].shape_dist_traveled <= w[1].shape_dist_traveled));
+    assert_eq!(
+        Some(ProjectionProblem::OutOfOrder),
+        inbound.stops[1].problem
+    );
+}
+
+fn shape_point(sequence: u64, lat: f64, lon: f64, dist: Option<f64>) -> ShapePoint {
+    ShapePoint {
+        shape_id: "s".to_string(),
+        shape_pt_lat: lat,
+        shape_pt_lon: lon,
+        shape_pt_sequence: sequence,
+        shape_dist_traveled: dist,
+    }
+}
+
+#[test]
+fn test_simplify_shape() {
+    // Wiggles of about 1 m along a line, then a 1 km detour
+    let mut points: Vec<ShapePoint> = (0..100)
+        .map(|i| {
+            let wiggle = if i % 2 == 0 { 0.00001 } else { 0.0 };
+            shape_point(i, wiggle, f64::from(i as u32) * 0.001, Some(i as f64))
+        })
+        .collect();
+    points.push(shape_point(100, 0.01, 0.1, Some(200.0)));
+    let refs: Vec<&ShapePoint> = points.iter().collect();
+
+    let simple = simplify(&refs, 5.0);
+    let sequences: Vec<u64> = simple.iter().map(|p| p.shape_pt_sequence).collect();
+    assert_eq!(vec![0, 99, 100], sequences);
+    assert_eq!(Some(99.0), simple[1].shape_dist_traveled);
+    assert_eq!(points.len(), simplify(&refs, 0.5).len());
+}
+
+#[test]
+fn test_densify_shape() {
+    // About 111 m apart
+    let points = [
+        shape_point(5, 0.0, 0.0, Some(0.0)),
+        shape_point(9, 0.0, 0.001, Some(100.0)),
+        shape_point(12, 0.0, 0.00105, None),
+    ];
+    let refs: Vec<&ShapePoint> = points.iter().collect();
+    let dense = densify(&refs, 30.0);
+    assert_eq!(6, dense.len());
+    let sequences: Vec<u64> = dense.iter().map(|p| p.shape_pt_sequence).collect();
+    assert_eq!(vec![1, 2, 3, 4, 5, 6], sequences);
+    assert_eq!(Some(25.0), dense[1].shape_dist_traveled);
+    assert!((dense[2].shape_pt_lon - 0.0005).abs() < 1e-12);
+    assert_eq!(Some(100.0), dense[4].shape_dist_traveled);
+    assert_eq!(None, dense[5].shape_dist_traveled);
+    assert_eq!(3, densify(&refs, 1000.0).len());
+}
+
+#[test]
+fn test_write_simplified_shapes() {
+    let mut feed = good_feed();
+    // shape_1 runs through (1, 1), (2, 4), (3, 9), (4, 16), the middle
+    // points being about 40 km off the line between its ends
+    simplify_shapes(&mut feed, 50_000.0);
+    assert_eq!(2, feed.shape("shape_1").len());
+
+    let mut out = Vec::new();
+    write_shapes(&mut out, &feed.shapes).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    assert!(text.starts_with(
+        "shape_id,shape_pt_lat,shape_pt_lon,shape_pt_sequence,shape_dist_traveled\nshape_1,1.0,1.0,1,\n"
+    ));
+    let read: Vec<ShapePoint> = csv::Reader::from_reader(text.as_bytes())
+        .deserialize()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(feed.shapes.len(), read.len());
+
+    densify_shapes(&mut feed, 50_000.0);
+    assert!(feed.shape("shape_1").len() > 10);
+}
@@ -0,0 +1,66 @@
+use transitfeed::{FeedReader, LocationType, StopFilter, StopIndex, WheelchairAccessible};
+
+const BEATTY_AIRPORT: (f64, f64) = (36.868446, -116.784582);
+
+fn stop_ids(stops: &[(&transitfeed::Stop, f64)]) -> Vec<String> {
+    stops.iter().map(|(s, _)| s.stop_id.clone()).collect()
+}
+
+#[test]
+fn test_nearest_stops() {
+    let feed = FeedReader::new("./examples/good_feed").load().unwrap();
+    let index = StopIndex::new(&feed);
+
+    let nearest = index.nearest(BEATTY_AIRPORT, 3, &StopFilter::default());
+    assert_eq!(3, nearest.len());
+    assert_eq!(0.0, nearest[0].1);
+    assert_eq!(0.0, nearest[1].1);
+    assert_eq!("BULLFROG", nearest[2].0.stop_id);
+    assert!(nearest[2].1 > 3200.0 && nearest[2].1 < 3400.0);
+
+    let stops = StopFilter::default().location_type(LocationType::Stop);
+    let nearest = index.nearest(BEATTY_AIRPORT, 2, &stops);
+    assert_eq!(vec!["BEATTY_AIRPORT", "BULLFROG"], stop_ids(&nearest));
+    assert_eq!(
+        feed.stops.len(),
+        index
+            .nearest(BEATTY_AIRPORT, 100, &StopFilter::default())
+            .len()
+    );
+}
+
+#[test]
+fn test_stops_within_radius() {
+    let feed = FeedReader::new("./examples/good_feed").load().unwrap();
+    let index = StopIndex::new(&feed);
+    let nadav = (36.914893, -116.76821);
+    let near = index.within_radius(nadav, 1000.0, &StopFilter::default());
+    assert_eq!(vec!["NADAV", "NANAA", "DADAN"], stop_ids(&near));
+    assert!(near.iter().all(|(_, d)| *d <= 1000.0));
+    assert!(index
+        .within_radius((0.0, 0.0), 1000.0, &StopFilter::default())
+        .is_empty());
+}
+
+#[test]
+fn test_stops_in_bbox() {
+    let feed = FeedReader::new("./examples/good_feed").load().unwrap();
+    let index = StopIndex::new(&feed);
+    let mut town: Vec<&str> = index
+        .in_bbox((36.9, -116.78), (36.92, -116.75), &StopFilter::default())
+        .iter()
+        .map(|s| s.stop_id.as_str())
+        .collect();
+    town.sort();
+    assert_eq!(vec!["DADAN", "EMSI", "NADAV", "NANAA", "STAGECOACH"], town);
+
+    let stations = StopFilter::default().location_type(LocationType::Station);
+    let found = index.in_bbox((36.0, -118.0), (37.0, -116.0), &stations);
+    assert_eq!(1, found.len());
+
+    let accessible =
+        StopFilter::default().wheelchair_boarding(WheelchairAccessible::SomeAccessibility);
+    assert!(index
+        .in_bbox((36.0, -118.0), (37.0, -116.0), &accessible)
+        .is_empty());
+}
@@ -0,0 +1,97 @@
+use chrono::NaiveDate;
+use transitfeed::{FeedReader, ServiceCalendar, TimeOffset, Timetable};
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn whole_day() -> std::ops::Range<TimeOffset> {
+    TimeOffset::from_hms(0, 0, 0)..TimeOffset::from_hms(24, 0, 0)
+}
+
+#[test]
+fn test_service_calendar() {
+    let feed = FeedReader::new("./examples/good_feed").load().unwrap();
+    let calendar = ServiceCalendar::new(&feed);
+    assert!(calendar.is_active("FULLW", date(2024, 6, 5)));
+    assert!(!calendar.is_active("WE", date(2024, 6, 5)));
+    assert!(calendar.is_active("WE", date(2024, 6, 8)));
+    // Removed in calendar_dates.txt
+    assert!(!calendar.is_active("FULLW", date(2007, 6, 4)));
+    assert!(!calendar.is_active("FULLW", date(2026, 1, 1)));
+    assert_eq!(2, calendar.active_services(date(2024, 6, 9)).len());
+}
+
+#[test]
+fn test_departures_from_station() {
+    let feed = FeedReader::new("./examples/good_feed").load().unwrap();
+    let timetable = Timetable::new(&feed);
+
+    let weekday = timetable.departures("BEATTY_AIRPORT_STATION", date(2024, 6, 5), whole_day());
+    let trips: Vec<_> = weekday.iter().map(|d| d.trip.trip_id.as_str()).collect();
+    // AB2 ends at the airport, so it only arrives there
+    assert_eq!(vec!["AB1"], trips);
+    assert_eq!("AB", weekday[0].route.route_id);
+    assert_eq!(Some("to Bullfrog"), weekday[0].headsign);
+    assert_eq!(TimeOffset::from_hms(8, 0, 0), weekday[0].departure_time);
+
+    let window = TimeOffset::from_hms(8, 0, 0)..TimeOffset::from_hms(14, 0, 0);
+    let weekend = timetable.departures("BEATTY_AIRPORT_STATION", date(2024, 6, 8), window);
+    let trips: Vec<_> = weekend.iter().map(|d| d.trip.trip_id.as_str()).collect();
+    assert_eq!(3, trips.len());
+    assert!(trips[..2].contains(&"AB1") && trips[..2].contains(&"AAMV1"));
+    assert_eq!("AAMV3", trips[2]);
+
+    assert!(timetable
+        .departures("BEATTY_AIRPORT", date(2007, 6, 4), whole_day())
+        .is_empty());
+}
+
+#[test]
+fn test_departures_headsigns_and_pickups() {
+    let feed = FeedReader::new("./examples/good_feed").load().unwrap();
+    let timetable = Timetable::new(&feed);
+
+    // STBA has no pickup at STAGECOACH and CITY2 ends there
+    let stagecoach = timetable.departures("STAGECOACH", date(2024, 6, 5), whole_day());
+    let trips: Vec<_> = stagecoach.iter().map(|d| d.trip.trip_id.as_str()).collect();
+    assert_eq!(vec!["CITY1"], trips);
+    assert_eq!(None, stagecoach[0].headsign);
+
+    let nanaa = timetable.departures("NANAA", date(2024, 6, 5), whole_day());
+    assert_eq!(Some("going to nadav"), nanaa[0].headsign);
+}
+
+#[test]
+fn test_departures_after_midnight() {
+    let mut feed = FeedReader::new("./examples/good_feed").load().unwrap();
+    for stop_time in feed.stop_times.iter_mut().filter(|st| st.trip_id == "AB1") {
+        stop_time.arrival_time.hours += 16;
+        stop_time.departure_time.hours += 16;
+    }
+    let timetable = Timetable::new(&feed);
+
+    let window = TimeOffset::from_hms(0, 0, 0)..TimeOffset::from_hms(1, 0, 0);
+    let departures = timetable.departures("BEATTY_AIRPORT", date(2024, 6, 6), window);
+    assert_eq!(1, departures.len());
+    assert_eq!(date(2024, 6, 5), departures[0].service_date);
+    assert_eq!(TimeOffset::from_hms(0, 0, 0), departures[0].departure_time);
+    assert_eq!(
+        TimeOffset::from_hms(24, 0, 0),
+        departures[0].stop_time.departure_time
+    );
+}
+
+#[test]
+fn test_departures_of_next_service_day() {
+    let feed = FeedReader::new("./examples/good_feed").load().unwrap();
+    let timetable = Timetable::new(&feed);
+
+    // Thursday's 08:00 departure of AB1 is at 32:00 from Wednesday
+    let window = TimeOffset::from_hms(24, 0, 0)..TimeOffset::from_hms(33, 0, 0);
+    let departures = timetable.departures("BEATTY_AIRPORT", date(2024, 6, 5), window);
+    assert_eq!(1, departures.len());
+    assert_eq!("AB1", departures[0].trip.trip_id);
+    assert_eq!(date(2024, 6, 6), departures[0].service_date);
+    assert_eq!(TimeOffset::from_hms(32, 0, 0), departures[0].departure_time);
+}